@@ -1,6 +1,101 @@
-#[derive(Hash, Eq, PartialEq, Clone, Copy)]
+use std::fmt;
+
+/// AccountTier selects the REST call counter limits Kraken applies
+/// to an account, following its verification level.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum AccountTier {
+    /// A counter of 15, decaying by 0.33 per second.
     Starter,
+    /// A counter of 20, decaying by 0.5 per second.
     Intermediate,
+    /// A counter of 20, decaying by 1 per second.
     Pro,
+    /// Limits agreed with Kraken which differ from the published tiers,
+    /// such as for subaccounts or institutional accounts. `api` limits
+    /// the REST call counter, and `trading` each pair's order counter.
+    Custom {
+        api: CounterLimit,
+        trading: CounterLimit,
+    },
+}
+
+/// CounterLimit is the maximum of one of Kraken's rate counters and how
+/// fast it decays. The rate is kept as the time it takes to decay by one,
+/// in microseconds, so that limits can be compared and hashed.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct CounterLimit {
+    max: u32,
+    micros_per_token: u64,
+}
+
+impl CounterLimit {
+    /// Returns a limit of `max`, decaying by `decay_per_sec`, which may be
+    /// fractional. `max` must be above zero, and `decay_per_sec` must be
+    /// a finite number above zero and at most one million.
+    pub fn new(max: u32, decay_per_sec: f64) -> Result<Self, InvalidLimitError> {
+        let micros = 1e6 / decay_per_sec;
+        // Rejects NaN as well, since it fails every comparison.
+        let valid_rate = decay_per_sec > 0.0 && micros.round() >= 1.0 && micros < u64::MAX as f64;
+        if max == 0 || !valid_rate {
+            return Err(InvalidLimitError { max, decay_per_sec });
+        }
+        Ok(Self {
+            max,
+            micros_per_token: micros.round() as u64,
+        })
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    pub fn decay_per_sec(&self) -> f64 {
+        1e6 / self.micros_per_token as f64
+    }
+}
+
+/// InvalidLimitError is returned when a CounterLimit would never
+/// let a request through, or would decay at no sensible rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidLimitError {
+    max: u32,
+    decay_per_sec: f64,
+}
+
+impl std::error::Error for InvalidLimitError {}
+
+impl fmt::Display for InvalidLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a counter of {} decaying by {} per second is not a valid limit",
+            self.max, self.decay_per_sec
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CounterLimit;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn keeps_fractional_rates() {
+        let limit = CounterLimit::new(40, 0.25).unwrap();
+        assert_eq!(limit.max(), 40);
+        assert_eq!(limit.decay_per_sec(), 0.25);
+        assert_eq!(limit, CounterLimit::new(40, 0.25).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_limits() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::MIN_POSITIVE, 1e7] {
+            assert!(CounterLimit::new(20, rate).is_err(), "{}", rate);
+        }
+        let err = CounterLimit::new(0, 1.0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a counter of 0 decaying by 1 per second is not a valid limit"
+        );
+    }
 }
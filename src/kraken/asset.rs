@@ -0,0 +1,273 @@
+use crate::kraken::payload::AssetInfoResponse;
+use crate::kraken::{Client, ClientError};
+use once_cell::sync::Lazy;
+use serde::de::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt;
+
+// Kraken's legacy names for assets listed before it
+// settled on altnames, mapped to those altnames.
+const LEGACY_NAMES: [(&str, &str); 17] = [
+    ("XETC", "ETC"),
+    ("XETH", "ETH"),
+    ("XLTC", "LTC"),
+    ("XMLN", "MLN"),
+    ("XREP", "REP"),
+    ("XXBT", "XBT"),
+    ("XXDG", "XDG"),
+    ("XXLM", "XLM"),
+    ("XXMR", "XMR"),
+    ("XXRP", "XRP"),
+    ("XZEC", "ZEC"),
+    ("ZAUD", "AUD"),
+    ("ZCAD", "CAD"),
+    ("ZEUR", "EUR"),
+    ("ZGBP", "GBP"),
+    ("ZJPY", "JPY"),
+    ("ZUSD", "USD"),
+];
+
+// Altnames which differ from the ticker used everywhere else.
+const TICKERS: [(&str, &str); 2] = [("XBT", "BTC"), ("XDG", "DOGE")];
+
+static FALLBACK: Lazy<AssetNormalizer> = Lazy::new(AssetNormalizer::new);
+
+/// Asset is a currency under the ticker most systems use, such as "BTC"
+/// for Kraken's "XXBT" or "XBT". Balances Kraken holds apart from the
+/// spot balance keep the suffix Kraken gives them, such as "S" for
+/// staked "DOT.S", "M" for opt-in rewards or "HOLD" for "USD.HOLD".
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Asset {
+    ticker: String,
+    suffix: Option<String>,
+}
+
+impl Asset {
+    /// Normalizes a name Kraken uses for an asset with
+    /// the built-in table of legacy names and tickers.
+    /// Use an AssetNormalizer loaded from the Assets endpoint
+    /// to also recognize assets listed after this crate was released.
+    pub fn from_kraken(name: &str) -> Self {
+        FALLBACK.normalize(name)
+    }
+
+    /// Returns the ticker, such as "BTC".
+    pub fn ticker(&self) -> &str {
+        &self.ticker
+    }
+
+    /// Returns the suffix of a balance held apart from
+    /// the spot balance, such as "S" for "DOT.S".
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    /// Returns the name Kraken keys the asset by, such as "XXBT" for BTC,
+    /// using the built-in table. See AssetNormalizer::kraken_name.
+    pub fn kraken_name(&self) -> String {
+        FALLBACK.kraken_name(self)
+    }
+
+    /// Returns the asset without its suffix, so "DOT.S" becomes "DOT".
+    pub fn spot(&self) -> Self {
+        Self {
+            ticker: self.ticker.clone(),
+            suffix: None,
+        }
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.suffix {
+            Some(suffix) => write!(f, "{}.{}", self.ticker, suffix),
+            None => write!(f, "{}", self.ticker),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Asset {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// Normalizing a ticker leaves it unchanged, so this
+// accepts Kraken's names as well as common tickers.
+impl<'de> Deserialize<'de> for Asset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Self::from_kraken(&name))
+    }
+}
+
+/// AssetNormalizer maps between the names Kraken uses for assets and
+/// Assets. It knows the legacy names Kraken still uses for older assets,
+/// such as "XXBT" and "ZUSD", and learns every other asset's name and
+/// altname from the Assets endpoint.
+#[derive(Debug, Clone)]
+pub struct AssetNormalizer {
+    // Every name Kraken uses for an asset, mapped to its altname.
+    altnames: HashMap<String, String>,
+    // Every altname, mapped to the name Kraken keys the asset by.
+    names: HashMap<String, String>,
+}
+
+impl AssetNormalizer {
+    /// Creates a normalizer which only knows the built-in table.
+    pub fn new() -> Self {
+        let mut normalizer = Self {
+            altnames: HashMap::new(),
+            names: HashMap::new(),
+        };
+        for (name, altname) in LEGACY_NAMES {
+            normalizer.insert(name, altname);
+        }
+        normalizer
+    }
+
+    /// Adds the assets in a response from the Assets endpoint
+    /// to the built-in table.
+    pub fn from_asset_info(resp: &AssetInfoResponse) -> Self {
+        let mut normalizer = Self::new();
+        for (name, info) in &resp.result {
+            normalizer.insert(name, &info.alt_name);
+        }
+        // Kraken gives some fiat currencies their legacy name as altname,
+        // such as "ZUSD" for ZUSD, so the built-in table takes precedence.
+        for (name, altname) in LEGACY_NAMES {
+            normalizer.insert(name, altname);
+        }
+        normalizer
+    }
+
+    /// Loads every asset from the Assets endpoint.
+    pub async fn load<A>(client: &Client<A>) -> Result<Self, ClientError> {
+        let resp = client.asset_info_response(Vec::new(), None).await?;
+        match resp.error.is_empty() {
+            true => Ok(Self::from_asset_info(&resp)),
+            false => Err(ClientError::Kraken(resp.error)),
+        }
+    }
+
+    fn insert(&mut self, name: &str, altname: &str) {
+        let (name, altname) = (name.to_ascii_uppercase(), altname.to_ascii_uppercase());
+        self.altnames.insert(name.clone(), altname.clone());
+        self.altnames.insert(altname.clone(), altname.clone());
+        self.names.insert(altname, name);
+    }
+
+    /// Returns the Asset for a name Kraken uses, which may be a legacy
+    /// name, an altname, or either followed by a suffix like ".S".
+    /// Names it doesn't know are kept as they are, in upper case.
+    pub fn normalize(&self, name: &str) -> Asset {
+        let name = name.trim().to_ascii_uppercase();
+        let (base, suffix) = match name.split_once('.') {
+            Some((base, suffix)) => (base, Some(suffix.to_owned())),
+            None => (name.as_str(), None),
+        };
+        let altname = self.altnames.get(base).map_or(base, String::as_str);
+        let ticker = TICKERS
+            .iter()
+            .find(|(alt, _)| *alt == altname)
+            .map_or(altname, |(_, ticker)| ticker);
+        Asset {
+            ticker: ticker.to_owned(),
+            suffix,
+        }
+    }
+
+    /// Returns the name Kraken keys the asset by, such as "XXBT" for
+    /// BTC. Kraken names suffixed balances by the altname, as in "XBT.M".
+    pub fn kraken_name(&self, asset: &Asset) -> String {
+        let altname = TICKERS
+            .iter()
+            .find(|(_, ticker)| *ticker == asset.ticker)
+            .map_or(asset.ticker.as_str(), |(alt, _)| alt);
+        match &asset.suffix {
+            Some(suffix) => format!("{}.{}", altname, suffix),
+            None => self
+                .names
+                .get(altname)
+                .cloned()
+                .unwrap_or_else(|| altname.to_owned()),
+        }
+    }
+}
+
+impl Default for AssetNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Asset, AssetNormalizer};
+    use crate::kraken::payload::AssetInfoResponse;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn normalizes_legacy_names_and_suffixes() {
+        let cases = [
+            ("XXBT", "BTC"),
+            ("XBT", "BTC"),
+            ("BTC", "BTC"),
+            ("ZUSD", "USD"),
+            ("XETH", "ETH"),
+            ("XXDG", "DOGE"),
+            ("XBT.M", "BTC.M"),
+            ("DOT.S", "DOT.S"),
+            ("USD.HOLD", "USD.HOLD"),
+            ("ADA", "ADA"),
+        ];
+        for (name, asset) in cases {
+            assert_eq!(Asset::from_kraken(name).to_string(), asset);
+        }
+        assert_eq!(
+            Asset::from_kraken("DOT.S").spot(),
+            Asset::from_kraken("DOT")
+        );
+    }
+
+    #[test]
+    fn learns_names_from_asset_info() {
+        let resp: AssetInfoResponse = serde_json::from_value(json!({
+            "error": [],
+            "result": {
+                "XXBT": {"aclass": "currency", "altname": "XBT", "decimals": 10, "display_decimals": 5},
+                "XNEW": {"aclass": "currency", "altname": "NEW", "decimals": 8, "display_decimals": 4}
+            }
+        }))
+        .unwrap();
+        let normalizer = AssetNormalizer::from_asset_info(&resp);
+        let asset = normalizer.normalize("XNEW");
+        assert_eq!(asset.ticker(), "NEW");
+        assert_eq!(normalizer.kraken_name(&asset), "XNEW");
+        let btc = normalizer.normalize("XXBT");
+        assert_eq!(normalizer.kraken_name(&btc), "XXBT");
+        assert_eq!(
+            normalizer.kraken_name(&Asset::from_kraken("XBT.M")),
+            "XBT.M"
+        );
+        assert_eq!(AssetNormalizer::new().kraken_name(&asset), "NEW");
+    }
+
+    #[test]
+    fn deserializes_kraken_names() {
+        let balances: std::collections::HashMap<Asset, String> =
+            serde_json::from_value(json!({"XXBT": "1.5", "ZUSD": "100"})).unwrap();
+        assert_eq!(balances[&Asset::from_kraken("BTC")], "1.5");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_normalized_names() {
+        assert_eq!(
+            serde_json::to_value(Asset::from_kraken("ZUSD")).unwrap(),
+            json!("USD")
+        );
+    }
+}
@@ -1,20 +1,99 @@
 use std::fmt;
+use std::str::FromStr;
 
-pub enum AssetPair {
-    EthUsd,
-    BtcUsd,
-    AlgoUsd,
-    DotUsd,
+/// AssetPair names a Kraken market, such as "XBTUSD". Kraken knows each
+/// pair by several names: its legacy name ("XXBTZUSD"), its altname
+/// ("XBTUSD") and its websocket name ("XBT/USD"). An AssetPair holds
+/// whichever it was given; use a PairRegistry to resolve it to the
+/// name Kraken keys the pair by in its responses.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct AssetPair(String);
+
+impl AssetPair {
+    /// Returns the pair's name, in upper case.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 impl fmt::Display for AssetPair {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let val = match self {
-            Self::EthUsd => "ETHUSD",
-            Self::BtcUsd => "BTCUSD",
-            Self::DotUsd => "DOTUSD",
-            Self::AlgoUsd => "ALGOUSD",
-        };
-        write!(f, "{}", val)
+        write!(f, "{}", self.0)
+    }
+}
+
+/// ParsePairError is returned when a string can't be a pair name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePairError(String);
+
+impl std::error::Error for ParsePairError {}
+
+impl fmt::Display for ParsePairError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a valid asset pair", self.0)
+    }
+}
+
+impl FromStr for AssetPair {
+    type Err = ParsePairError;
+
+    /// Parses a pair name, ignoring surrounding whitespace and case.
+    /// Names may contain letters, digits, '.' and a '/' between
+    /// the base and quote assets, as websocket names do.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        let valid_char = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '/';
+        let valid = !name.is_empty()
+            && name.chars().all(valid_char)
+            && name.matches('/').count() <= 1
+            && !name.starts_with('/')
+            && !name.ends_with('/');
+        match valid {
+            true => Ok(Self(name.to_ascii_uppercase())),
+            false => Err(ParsePairError(s.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for AssetPair {
+    type Error = ParsePairError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<AssetPair> for String {
+    fn from(pair: AssetPair) -> Self {
+        pair.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AssetPair;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_any_pair_name() {
+        let pair: AssetPair = " xbt/usd ".parse().unwrap();
+        assert_eq!(pair.to_string(), "XBT/USD");
+        let pair: AssetPair = "XXBTZUSD".parse().unwrap();
+        assert_eq!(pair.as_str(), "XXBTZUSD");
+        assert!("".parse::<AssetPair>().is_err());
+        assert!("XBT USD".parse::<AssetPair>().is_err());
+        assert!("XBT/USD/EUR".parse::<AssetPair>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_as_a_string() {
+        use serde_json::json;
+
+        let pair: AssetPair = serde_json::from_value(json!("dotusd")).unwrap();
+        assert_eq!(serde_json::to_value(&pair).unwrap(), json!("DOTUSD"));
+        assert!(serde_json::from_value::<AssetPair>(json!("DOT USD")).is_err());
     }
 }
@@ -0,0 +1,11 @@
+use crate::kraken::env::KrakenCredentials;
+
+/// Public marks a Client built without credentials,
+/// which can only call public endpoints.
+pub struct Public;
+
+/// Authenticated marks a Client holding the credentials
+/// used to sign requests to private endpoints.
+pub struct Authenticated {
+    pub(crate) creds: KrakenCredentials,
+}
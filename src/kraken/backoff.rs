@@ -0,0 +1,31 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// Backoff is the exponential backoff with jitter shared by
+/// RetryPolicy and the websocket ReconnectPolicy.
+pub(crate) struct Backoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+}
+
+impl Backoff {
+    /// Returns the delay to wait before the given attempt,
+    /// counting from 1, including jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self.base_delay(attempt).as_secs_f64();
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        // Pick a point uniformly in [base * (1 - jitter), base].
+        let factor = 1.0 - jitter * rand::thread_rng().gen::<f64>();
+        Duration::from_secs_f64(base * factor)
+    }
+
+    fn base_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let initial = self.initial_delay.as_secs_f64();
+        let delay = initial * self.multiplier.max(1.0).powi(exponent);
+        let max = self.max_delay.as_secs_f64();
+        Duration::from_secs_f64(delay.min(max))
+    }
+}
@@ -1,253 +1,873 @@
+use crate::kraken::authentication::{Authenticated, Public};
+use crate::kraken::client_builder::ClientBuilder;
+use crate::kraken::client_error::ClientError;
+use crate::kraken::endpoints::ApiUrl;
 use crate::kraken::env::KrakenCredentials;
 use crate::kraken::payload::{
-    self, AssetInfoInput, AssetInfoResponse, AssetPairsInfo, AssetPairsInput, AssetPairsResponse,
-    RawRecentSpreadsResponse, RawTickerResponse, RecentSpreadsInput, RecentSpreadsResponse,
-    SerializableAssetPairsInput, TickerInput, TickerResponse,
+    self, AddOrderInput, AddOrderResponse, AssetInfo, AssetInfoInput, AssetInfoResponse,
+    AssetPairsInput, AssetPairsResponse, AssetPairsShape, CancelOrderInput, CancelOrderResponse,
+    OrdersByUserRefResponse, RawRecentSpreadsResponse, RawTickerResponse, RecentSpreadsInput,
+    RecentSpreadsResponse, SerializableAddOrderInput, SerializableAssetPairsInput, TickerInput,
+    TickerResponse,
 };
-use crate::kraken::ratelimiter::LeakyBucket;
+use crate::kraken::ratelimiter::{endpoint_cost, TradingCounter};
 use crate::kraken::request_builder::{ParamEncoding, PrivacyLevel, RequestBuilder};
-use crate::kraken::signature::get_kraken_signature;
-use crate::kraken::AccountTier;
+use crate::kraken::transport::{Retry, Transport};
+use crate::kraken::{AccountTier, Asset, AssetNormalizer};
 use crate::kraken::{
-    endpoint, AssetPair, ACCOUNT_BALANCE, ASSET_INFO, ASSET_PAIRS, OPEN_ORDERS, RECENT_SPREADS,
-    SYSTEM_STATUS, SYSTEM_TIME, TICKER, TRADE_BALANCE,
+    AssetPair, ACCOUNT_BALANCE, ADD_ORDER, ASSET_INFO, ASSET_PAIRS, CANCEL_ORDER, CLOSED_ORDERS,
+    OPEN_ORDERS, RECENT_SPREADS, SYSTEM_STATUS, SYSTEM_TIME, TICKER, TRADE_BALANCE,
 };
-use chrono::prelude::*;
-use reqwest::header::{HeaderValue, CONTENT_TYPE};
 use reqwest::Method;
-use std::error::Error;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
 
-pub struct Client {
-    http: reqwest::Client,
-    api_key: String,
-    private_key: String,
-    rate_limiter: LeakyBucket,
+/// Client talks to the Kraken spot REST API. The type parameter records
+/// whether it holds credentials: every Client can call the public
+/// endpoints, but only a `Client<Authenticated>` can call private ones.
+///
+/// A client given credentials can call private endpoints:
+///
+/// ```no_run
+/// use kraken_http::{AccountTier, Client, KrakenCredentials};
+///
+/// async fn balance(creds: KrakenCredentials) {
+///     let client = Client::builder(AccountTier::Starter)
+///         .credentials(creds)
+///         .build()
+///         .unwrap();
+///     client.account_balance().await.unwrap();
+/// }
+/// ```
+///
+/// One built without them is a PublicClient, which has no private
+/// methods, so the mistake is caught when compiling:
+///
+/// ```compile_fail
+/// use kraken_http::{AccountTier, Client};
+///
+/// async fn balance() {
+///     let client = Client::builder(AccountTier::Starter).build().unwrap();
+///     client.account_balance().await.unwrap();
+/// }
+/// ```
+pub struct Client<A = Authenticated> {
+    transport: Transport,
+    auth: A,
+    trading: TradingCounter,
+    api_url: ApiUrl,
 }
 
-impl Client {
-    pub fn new(creds: KrakenCredentials, tier: AccountTier) -> Self {
-        // First, create a new reqwest client.
-        let client = reqwest::Client::new();
-        // Set the API Key and the Private Key.
+/// PublicClient is a Client built without credentials.
+pub type PublicClient = Client<Public>;
+
+impl Client<Public> {
+    /// Creates a client without credentials, for calling public endpoints.
+    pub fn public(tier: AccountTier) -> Self {
+        ClientBuilder::new(tier).build().expect("Client::public()")
+    }
+
+    /// Returns a ClientBuilder for configuring the client beyond the
+    /// defaults used by Client::new. The builder produces a PublicClient
+    /// unless credentials are given to it.
+    pub fn builder(tier: AccountTier) -> ClientBuilder {
+        ClientBuilder::new(tier)
+    }
+}
+
+impl<A> Client<A> {
+    pub(crate) fn from_builder(builder: ClientBuilder<A>, transport: Transport) -> Self {
         Self {
-            http: client,
-            api_key: creds.api_key().to_string(),
-            private_key: creds.private_key().to_string(),
-            rate_limiter: LeakyBucket::new(tier),
+            transport,
+            trading: TradingCounter::new(builder.tier),
+            api_url: ApiUrl::spot(builder.base_url, &builder.api_version),
+            auth: builder.auth,
         }
     }
 
-    async fn use_rate_limit(&self, count: usize) {
-        self.rate_limiter.use_rate_limit(count).await
+    async fn send_raw<F, B>(
+        &self,
+        path: &str,
+        retry: Retry,
+        build: B,
+    ) -> Result<Vec<u8>, ClientError>
+    where
+        F: Serialize,
+        B: Fn(&str) -> Result<RequestBuilder<F>, ClientError>,
+    {
+        let cost = endpoint_cost(path);
+        self.transport
+            .send(path, cost, retry, false, |path, _| build(path))
+            .await
     }
 
-    fn nonce(&self) -> String {
-        let utc: DateTime<Utc> = Utc::now();
-        let ms = utc.timestamp_millis();
-        ms.to_string()
+    async fn send<F, R, B>(&self, path: &str, retry: Retry, build: B) -> Result<R, ClientError>
+    where
+        F: Serialize,
+        R: DeserializeOwned,
+        B: Fn(&str) -> Result<RequestBuilder<F>, ClientError>,
+    {
+        let body = self.send_raw(path, retry, build).await?;
+        serde_json::from_slice(&body).map_err(ClientError::Decode)
     }
 
-    pub async fn server_time(&self) -> Result<payload::ServerTimeResponse, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let client = &self.http;
-        let req = RequestBuilder::<()> {
-            method: Method::GET,
-            url: endpoint(SYSTEM_TIME),
-            params: None,
-            param_encoding: ParamEncoding::FormEncoded,
-            privacy_level: PrivacyLevel::Public,
-        };
-        let resp = req.execute(client).await?;
-        Ok(resp)
+    /// Returns how many points are left on the REST call counter. Compare
+    /// it against endpoint_cost to see whether a call would have to wait.
+    pub async fn remaining_rate_limit(&self) -> usize {
+        self.transport.rate_limiter.remaining().await
     }
 
-    pub async fn system_status(&self) -> Result<payload::SystemStatusResponse, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let client = &self.http;
-        let req = RequestBuilder::<()> {
-            method: Method::GET,
-            url: endpoint(SYSTEM_STATUS),
-            params: None,
-            param_encoding: ParamEncoding::QueryEncoded,
+    fn build_public_request<F: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        param_encoding: ParamEncoding,
+        params: Option<F>,
+    ) -> RequestBuilder<F> {
+        RequestBuilder {
+            method,
+            url: self.api_url.endpoint(path),
+            params,
+            param_encoding,
             privacy_level: PrivacyLevel::Public,
-        };
-        let resp = req.execute(client).await?;
-        Ok(resp)
+        }
     }
 
-    pub async fn account_balance(&self) -> Result<payload::AccountBalanceResponse, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let nonce = self.nonce();
-        let client = &self.http;
-        let req = RequestBuilder {
-            method: Method::POST,
-            url: endpoint(ACCOUNT_BALANCE),
-            param_encoding: ParamEncoding::FormEncoded,
-            params: Some(payload::AccountBalanceInput {
-                nonce: nonce.clone(),
-            }),
-            privacy_level: PrivacyLevel::Private {
-                nonce,
-                api_key: self.api_key.clone(),
-                private_key: self.private_key.clone(),
-            },
-        };
-        let resp = req.execute(client).await?;
-        Ok(resp)
+    /// Calls a public endpoint this crate has no bindings for, such as
+    /// "/public/Depth", sending `params` in the query string. Pass `()`
+    /// when the endpoint takes no parameters. The whole response, including
+    /// Kraken's "error" array, is decoded into `R`, which may be
+    /// `serde_json::Value` to skip defining a type for it. The request is
+    /// rate limited and retried like the requests this crate has bindings for.
+    pub async fn public_request<P, R>(&self, path: &str, params: P) -> Result<R, ClientError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        self.send(path, Retry::Idempotent, |path| {
+            Ok(self.build_public_request(
+                Method::GET,
+                path,
+                ParamEncoding::QueryEncoded,
+                Some(&params),
+            ))
+        })
+        .await
+    }
+
+    pub async fn server_time(&self) -> Result<payload::ServerTimeResponse, ClientError> {
+        self.send(SYSTEM_TIME, Retry::Idempotent, |path| {
+            Ok(
+                self.build_public_request::<()>(
+                    Method::GET,
+                    path,
+                    ParamEncoding::FormEncoded,
+                    None,
+                ),
+            )
+        })
+        .await
+    }
+
+    pub async fn system_status(&self) -> Result<payload::SystemStatusResponse, ClientError> {
+        self.send(SYSTEM_STATUS, Retry::Idempotent, |path| {
+            Ok(self.build_public_request::<()>(
+                Method::GET,
+                path,
+                ParamEncoding::QueryEncoded,
+                None,
+            ))
+        })
+        .await
     }
 
+    /// Returns information about the assets, or about every asset if
+    /// `assets` is empty, keyed by the normalized asset.
     pub async fn asset_info(
         &self,
-        asset: Option<String>,
+        assets: Vec<Asset>,
         asset_class: Option<String>,
-    ) -> Result<AssetInfoResponse, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let client = &self.http;
-        let req = RequestBuilder {
-            method: Method::GET,
-            url: endpoint(ASSET_INFO),
-            param_encoding: ParamEncoding::FormEncoded,
-            params: Some(AssetInfoInput { asset, asset_class }),
-            privacy_level: PrivacyLevel::Public,
+    ) -> Result<HashMap<Asset, AssetInfo>, ClientError> {
+        let names = assets.iter().map(Asset::kraken_name).collect();
+        let resp = self.asset_info_response(names, asset_class).await?;
+        if !resp.error.is_empty() {
+            return Err(ClientError::Kraken(resp.error));
+        }
+        // Learn the names of assets missing from the built-in table.
+        let normalizer = AssetNormalizer::from_asset_info(&resp);
+        let assets = resp
+            .result
+            .into_iter()
+            .map(|(name, info)| (normalizer.normalize(&name), info))
+            .collect();
+        Ok(assets)
+    }
+
+    pub(crate) async fn asset_info_response(
+        &self,
+        assets: Vec<String>,
+        asset_class: Option<String>,
+    ) -> Result<AssetInfoResponse, ClientError> {
+        let params = AssetInfoInput {
+            asset: match assets.len() {
+                0 => None,
+                _ => Some(assets.join(",")),
+            },
+            asset_class,
         };
-        let resp: AssetInfoResponse = req.execute(client).await?;
-        Ok(resp)
+        self.send(ASSET_INFO, Retry::Idempotent, |path| {
+            Ok(self.build_public_request(
+                Method::GET,
+                path,
+                ParamEncoding::QueryEncoded,
+                Some(&params),
+            ))
+        })
+        .await
     }
 
     pub async fn recent_spreads(
         &self,
         pair: String,
         since: Option<u64>,
-    ) -> Result<RecentSpreadsResponse, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let client = &self.http;
-        let req = RequestBuilder {
-            method: Method::GET,
-            url: endpoint(RECENT_SPREADS),
-            param_encoding: ParamEncoding::QueryEncoded,
-            params: Some(RecentSpreadsInput { pair, since }),
-            privacy_level: PrivacyLevel::Public,
-        };
-        let resp: RawRecentSpreadsResponse = req.execute(client).await?;
-        Ok(RecentSpreadsResponse::from(resp))
+    ) -> Result<RecentSpreadsResponse, ClientError> {
+        let resp: RawRecentSpreadsResponse = self
+            .send(RECENT_SPREADS, Retry::Idempotent, |path| {
+                let params = RecentSpreadsInput {
+                    pair: pair.clone(),
+                    since,
+                };
+                Ok(self.build_public_request(
+                    Method::GET,
+                    path,
+                    ParamEncoding::QueryEncoded,
+                    Some(params),
+                ))
+            })
+            .await?;
+        RecentSpreadsResponse::try_from(resp)
     }
 
+    /// Returns everything Kraken reports about the pairs, or every pair if
+    /// `pairs` is empty. `country_code`, such as "GB" or "US:TX", limits
+    /// the pairs to those which can be traded from that jurisdiction.
     pub async fn asset_pairs(
         &self,
         pairs: Vec<String>,
-        info: Option<AssetPairsInfo>,
-    ) -> Result<AssetPairsResponse, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let client = &self.http;
-        let user_input = AssetPairsInput { pairs, info };
-        let serializable_input = SerializableAssetPairsInput::from(user_input);
-        let req = RequestBuilder {
-            method: Method::GET,
-            url: endpoint(ASSET_PAIRS),
-            param_encoding: ParamEncoding::QueryEncoded,
-            params: Some(serializable_input),
-            privacy_level: PrivacyLevel::Public,
+        country_code: Option<String>,
+    ) -> Result<AssetPairsResponse, ClientError> {
+        self.asset_pairs_info(pairs, country_code).await
+    }
+
+    /// Returns part of what Kraken reports about the pairs, as selected
+    /// by the shape: PairLeverage, PairFees, PairMargin or AssetPairInfo.
+    pub async fn asset_pairs_info<T: AssetPairsShape>(
+        &self,
+        pairs: Vec<String>,
+        country_code: Option<String>,
+    ) -> Result<AssetPairsResponse<T>, ClientError> {
+        let user_input = AssetPairsInput {
+            pairs,
+            info: Some(T::INFO),
+            country_code,
         };
-        let resp = req.execute(client).await?;
-        Ok(resp)
+        let serializable_input = SerializableAssetPairsInput::from(user_input);
+        self.send(ASSET_PAIRS, Retry::Idempotent, |path| {
+            Ok(self.build_public_request(
+                Method::GET,
+                path,
+                ParamEncoding::QueryEncoded,
+                Some(&serializable_input),
+            ))
+        })
+        .await
     }
 
-    pub async fn ticker(&self, asset_pair: AssetPair) -> Result<TickerResponse, Box<dyn Error>> {
-        self.use_rate_limit(1).await;
-        let pair = asset_pair.to_string();
-        let client = &self.http;
-        let req = RequestBuilder {
-            method: Method::GET,
-            url: endpoint(TICKER),
-            param_encoding: ParamEncoding::QueryEncoded,
-            params: Some(TickerInput { pair }),
-            privacy_level: PrivacyLevel::Public,
+    /// Returns the ticker of each pair, or of every pair if `pairs` is
+    /// empty, in one request. The result is keyed by the name Kraken
+    /// gives each pair, which a PairRegistry can resolve names to.
+    /// A ticker which can't be parsed is returned in `skipped` instead.
+    pub async fn ticker(&self, pairs: Vec<AssetPair>) -> Result<TickerResponse, ClientError> {
+        let params = TickerInput {
+            pair: match pairs.len() {
+                0 => None,
+                _ => Some(
+                    pairs
+                        .iter()
+                        .map(AssetPair::as_str)
+                        .collect::<Vec<_>>()
+                        .join(","),
+                ),
+            },
         };
-        let resp: RawTickerResponse = req.execute(client).await?;
-        let ticker = TickerResponse::try_from(resp)?;
-        Ok(ticker)
+        let resp: RawTickerResponse = self
+            .send(TICKER, Retry::Idempotent, |path| {
+                Ok(self.build_public_request(
+                    Method::GET,
+                    path,
+                    ParamEncoding::QueryEncoded,
+                    Some(&params),
+                ))
+            })
+            .await?;
+        Ok(TickerResponse::from(resp))
     }
 
     ///////////////////////////////////////////////////////////////////////////
     // Everything under this line does not strongly type their responses. /////
     ///////////////////////////////////////////////////////////////////////////
 
-    pub async fn open_orders(
+    pub async fn debug_recent_spreads(
         &self,
-        trades: Option<bool>,
-        user_ref: Option<u32>,
-    ) -> Result<String, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let nonce = self.nonce();
-        let method = Method::POST;
-        let api_key = &self.api_key;
-        let content_type = "application/x-www-form-urlencoded; charset=utf-8";
-        let url = endpoint(OPEN_ORDERS);
-        let private_key = self.private_key.clone();
-        let form_param = payload::OpenOrdersInput {
-            nonce: nonce.clone(),
-            trades,
-            user_ref,
+        pair: String,
+        since: Option<u64>,
+    ) -> Result<String, ClientError> {
+        let body = self
+            .send_raw(RECENT_SPREADS, Retry::Idempotent, |path| {
+                let params = RecentSpreadsInput {
+                    pair: pair.clone(),
+                    since,
+                };
+                Ok(self.build_public_request(
+                    Method::GET,
+                    path,
+                    ParamEncoding::QueryEncoded,
+                    Some(params),
+                ))
+            })
+            .await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+impl Client<Authenticated> {
+    pub fn new(creds: KrakenCredentials, tier: AccountTier) -> Self {
+        // Like reqwest::Client::new, this panics if the
+        // HTTP client can't be built with the default options.
+        ClientBuilder::new(tier)
+            .credentials(creds)
+            .build()
+            .expect("Client::new()")
+    }
+
+    // Sends a signed request to the private endpoint at `path`, as send_raw
+    // does for public ones. `params` receives each attempt's nonce.
+    async fn send_private_raw<F, P>(
+        &self,
+        path: &str,
+        retry: Retry,
+        params: P,
+    ) -> Result<Vec<u8>, ClientError>
+    where
+        F: Serialize,
+        P: Fn(String) -> F,
+    {
+        let cost = endpoint_cost(path);
+        self.transport
+            .send(path, cost, retry, true, |path, nonce| {
+                // Signed attempts always have a nonce.
+                let nonce = nonce.unwrap_or_default();
+                Ok(self.build_private_request(path, nonce, &params))
+            })
+            .await
+    }
+
+    async fn send_private<F, R, P>(
+        &self,
+        path: &str,
+        retry: Retry,
+        params: P,
+    ) -> Result<R, ClientError>
+    where
+        F: Serialize,
+        R: DeserializeOwned,
+        P: Fn(String) -> F,
+    {
+        let body = self.send_private_raw(path, retry, params).await?;
+        serde_json::from_slice(&body).map_err(ClientError::Decode)
+    }
+
+    // Builds a signed POST request to a private endpoint. `params`
+    // receives the nonce, which must also be sent in the form body.
+    fn build_private_request<F, P>(&self, path: &str, nonce: String, params: P) -> RequestBuilder<F>
+    where
+        F: Serialize,
+        P: FnOnce(String) -> F,
+    {
+        let creds = &self.auth.creds;
+        RequestBuilder {
+            method: Method::POST,
+            url: self.api_url.endpoint(path),
+            param_encoding: ParamEncoding::FormEncoded,
+            params: Some(params(nonce.clone())),
+            privacy_level: PrivacyLevel::Private {
+                nonce,
+                api_key: creds.api_key().clone(),
+                private_key: creds.private_key().clone(),
+                uri_path: self.api_url.signed_path(path),
+            },
+        }
+    }
+
+    /// Calls a private endpoint this crate has no bindings for, such as
+    /// "/private/Ledgers", signing the request and adding a nonce to
+    /// `params`, which must serialize as a struct or a map, or be `()`.
+    /// The response is decoded into `R`, as with public_request.
+    /// The request is rate limited by the endpoint's cost but never
+    /// retried, since the endpoint might not be safe to call twice.
+    /// Orders placed this way are not counted by the trading counter.
+    pub async fn private_request<P, R>(&self, path: &str, params: P) -> Result<R, ClientError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        self.send_private(path, Retry::Never, |nonce| payload::RawPrivateInput {
+            nonce,
+            params: &params,
+        })
+        .await
+    }
+
+    pub async fn account_balance(&self) -> Result<payload::AccountBalanceResponse, ClientError> {
+        self.send_private(ACCOUNT_BALANCE, Retry::Idempotent, |nonce| {
+            payload::AccountBalanceInput { nonce }
+        })
+        .await
+    }
+
+    /// Places an order, first waiting until the pair's trading counter
+    /// has room for it. The request is not retried after a transient
+    /// failure, since the order may have been placed even though no
+    /// response arrived, unless the RetryPolicy has `dedup_orders` set
+    /// and the order has a userref. Then the open and closed orders are
+    /// checked for the userref before the order is sent again, and the
+    /// matching orders are returned if it was placed after all.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            name = "kraken",
+            skip_all,
+            fields(endpoint = ADD_ORDER, pair = %order.pair)
+        )
+    )]
+    pub async fn add_order(&self, order: &AddOrderInput) -> Result<AddOrderResponse, ClientError> {
+        let retry = &self.transport.retry;
+        let dedup = match (retry.dedup_orders, order.userref) {
+            (true, Some(userref)) => Some(userref),
+            _ => None,
+        };
+        let mut retries = 0;
+        let body = loop {
+            self.trading.place_order(&order.pair).await;
+            self.transport
+                .use_rate_limit(endpoint_cost(ADD_ORDER))
+                .await;
+            let nonce = self.transport.nonce().await?;
+            let req = self.build_private_request(ADD_ORDER, nonce, |nonce| {
+                SerializableAddOrderInput::new(nonce, order)
+            });
+            let attempt = self.transport.attempt(req).await;
+            let userref = match dedup {
+                Some(userref) if attempt.retryable && retry.should_retry(retries) => userref,
+                _ => break attempt.body?,
+            };
+            retries += 1;
+            self.transport.wait_to_retry(retries).await;
+            if let Some(placed) = self.find_order(userref).await? {
+                return Ok(placed);
+            }
         };
-        // Next, we have to attach the API Key header.
-        let mut req = self
-            .http
-            .request(method, url)
-            .form(&form_param)
-            .header("API-Key", api_key)
-            .header(CONTENT_TYPE, content_type)
-            .build()?;
-        let signature = get_kraken_signature(nonce, private_key, &req);
-        // We also need to attach the API-Sign header.
-        let api_sign = HeaderValue::from_str(&signature).unwrap();
-        req.headers_mut().insert("API-Sign", api_sign);
-        let resp = self.http.execute(req).await?.text().await?;
+        let resp: AddOrderResponse = serde_json::from_slice(&body).map_err(ClientError::Decode)?;
+        if let Some(result) = &resp.result {
+            self.trading.orders_placed(&order.pair, &result.txid);
+        }
         Ok(resp)
     }
 
-    pub async fn trade_balance(&self, asset: Option<String>) -> Result<String, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let nonce = self.nonce();
-        let method = Method::POST;
-        let api_key = &self.api_key;
-        let content_type = "application/x-www-form-urlencoded; charset=utf-8";
-        let url = endpoint(TRADE_BALANCE);
-        let private_key = self.private_key.clone();
-        let form_param = payload::TradeBalanceInput {
-            nonce: nonce.clone(),
-            asset,
-        };
-        // Next, we have to attach the API Key header.
-        let mut req = self
-            .http
-            .request(method, url)
-            .form(&form_param)
-            .header("API-Key", api_key)
-            .header(CONTENT_TYPE, content_type)
-            .build()?;
-        let signature = get_kraken_signature(nonce, private_key, &req);
-        // We also need to attach the API-Sign header.
-        let api_sign = HeaderValue::from_str(&signature).unwrap();
-        req.headers_mut().insert("API-Sign", api_sign);
-        let resp = self.http.execute(req).await?.text().await?;
+    /// Returns how many points are left on the pair's trading counter.
+    /// Placing an order costs 1, and cancelling one costs up to 8
+    /// depending on how recently it was placed.
+    pub fn remaining_trading_limit(&self, pair: &str) -> f64 {
+        self.trading.remaining(pair)
+    }
+
+    // Looks for open or closed orders with the userref.
+    async fn find_order(&self, userref: i32) -> Result<Option<AddOrderResponse>, ClientError> {
+        for path in [OPEN_ORDERS, CLOSED_ORDERS] {
+            let resp: OrdersByUserRefResponse = self
+                .send_private(path, Retry::Idempotent, |nonce| payload::OpenOrdersInput {
+                    nonce,
+                    trades: None,
+                    user_ref: Some(userref),
+                })
+                .await?;
+            if let Some(placed) = resp.into_add_order_response() {
+                return Ok(Some(placed));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Cancels the order with the given transaction ID, or
+    /// every order with the given userref.
+    pub async fn cancel_order(&self, txid: String) -> Result<CancelOrderResponse, ClientError> {
+        let resp: CancelOrderResponse = self
+            .send_private(CANCEL_ORDER, Retry::Idempotent, |nonce| CancelOrderInput {
+                nonce,
+                txid: txid.clone(),
+            })
+            .await?;
+        if resp.result.is_some() {
+            self.trading.order_cancelled(&txid);
+        }
         Ok(resp)
     }
 
-    pub async fn debug_recent_spreads(
+    // The private endpoints below do not strongly type their responses yet.
+
+    pub async fn open_orders(
         &self,
-        pair: String,
-        since: Option<u64>,
-    ) -> Result<String, reqwest::Error> {
-        self.use_rate_limit(1).await;
-        let client = &self.http;
-        let req = RequestBuilder {
-            method: Method::GET,
-            url: endpoint(RECENT_SPREADS),
-            param_encoding: ParamEncoding::QueryEncoded,
-            params: Some(RecentSpreadsInput { pair, since }),
-            privacy_level: PrivacyLevel::Public,
+        trades: Option<bool>,
+        user_ref: Option<i32>,
+    ) -> Result<String, ClientError> {
+        let body = self
+            .send_private_raw(OPEN_ORDERS, Retry::Idempotent, |nonce| {
+                payload::OpenOrdersInput {
+                    nonce,
+                    trades,
+                    user_ref,
+                }
+            })
+            .await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    pub async fn trade_balance(&self, asset: Option<String>) -> Result<String, ClientError> {
+        let body = self
+            .send_private_raw(TRADE_BALANCE, Retry::Idempotent, |nonce| {
+                payload::TradeBalanceInput {
+                    nonce,
+                    asset: asset.clone(),
+                }
+            })
+            .await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kraken::{
+        AccountTier, AddOrderInput, Asset, AssetPair, AssetStatus, Client, KrakenCredentials,
+        Middleware, OrderSide, OrderType, RequestInfo, ResponseInfo, RetryPolicy,
+    };
+    use bigdecimal::BigDecimal;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    const PRIVATE_KEY: &str =
+        "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==";
+
+    type Requests = Arc<Mutex<Vec<String>>>;
+
+    // Serves one canned (status, body) response per connection, in order,
+    // and records the request line and body of every request received.
+    async fn serve(responses: Vec<(u16, &'static str)>) -> (Url, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let requests = Requests::default();
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+                let mut chunk = [0; 4096];
+                let request = loop {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| {
+                                line.to_lowercase()
+                                    .strip_prefix("content-length: ")
+                                    .map(str::to_owned)
+                            })
+                            .map_or(0, |len| len.parse().unwrap());
+                        if body.len() >= length {
+                            let line = head.lines().next().unwrap();
+                            break format!("{} {}", line, body);
+                        }
+                    }
+                };
+                seen.lock().unwrap().push(request);
+                let resp = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(resp.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy {
+            initial_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        }
+    }
+
+    // Records the path, API key header and outcome of every request.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Middleware for Recorder {
+        fn after_response(&self, req: &RequestInfo, resp: &ResponseInfo) {
+            let line = format!(
+                "{} {} {:?} {:?} {:?}",
+                req.path, req.private, req.headers["API-Key"], resp.status, resp.errors
+            );
+            self.0.lock().unwrap().push(line);
+        }
+    }
+
+    fn client(url: Url, retry: RetryPolicy) -> Client {
+        let creds = KrakenCredentials::new("key".to_owned(), PRIVATE_KEY.to_owned());
+        Client::builder(AccountTier::Pro)
+            .base_url(url)
+            .retry_policy(retry)
+            .credentials(creds)
+            .build()
+            .unwrap()
+    }
+
+    fn order(userref: Option<i32>) -> AddOrderInput {
+        AddOrderInput {
+            pair: "XBTUSD".to_owned(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Market,
+            volume: BigDecimal::from(1),
+            price: None,
+            price2: None,
+            leverage: None,
+            userref,
+            oflags: None,
+            validate: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures() {
+        let time = r#"{"error":[],"result":{"unixtime":1688669448,"rfc1123":"Thu, 06 Jul 23 18:50:48 +0000"}}"#;
+        let (url, requests) = serve(vec![
+            (503, ""),
+            (200, r#"{"error":["EAPI:Rate limit exceeded"]}"#),
+            (200, time),
+        ])
+        .await;
+        let resp = client(url, fast_retries()).server_time().await.unwrap();
+        assert_eq!(resp.result.unwrap().unix_time, 1688669448);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn refreshes_the_nonce_on_retry() {
+        let balance = r#"{"error":[],"result":{"ZUSD":"1.0"}}"#;
+        let (url, requests) = serve(vec![(502, ""), (200, balance)]).await;
+        client(url, fast_retries()).account_balance().await.unwrap();
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("POST /0/private/Balance"));
+        assert!(requests[0] != requests[1]);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_order_placement() {
+        let unavailable = r#"{"error":["EService:Unavailable"]}"#;
+        let (url, requests) = serve(vec![(200, unavailable)]).await;
+        let resp = client(url, fast_retries())
+            .add_order(&order(Some(7)))
+            .await
+            .unwrap();
+        assert_eq!(resp.error, vec!["EService:Unavailable"]);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn finds_orders_placed_before_a_failure() {
+        let unavailable = r#"{"error":["EService:Unavailable"]}"#;
+        let open = r#"{"error":[],"result":{"open":{"OQCLML-BW3P3-BUCMWZ":{"descr":{"order":"buy 1.00000000 XBTUSD @ market"}}}}}"#;
+        let (url, requests) = serve(vec![(200, unavailable), (200, open)]).await;
+        let retry = RetryPolicy {
+            dedup_orders: true,
+            ..fast_retries()
         };
-        let resp = req.debug(client).await?;
-        Ok(resp)
+        let resp = client(url, retry).add_order(&order(Some(7))).await.unwrap();
+        let result = resp.result.unwrap();
+        assert_eq!(result.txid, vec!["OQCLML-BW3P3-BUCMWZ"]);
+        let requests = requests.lock().unwrap();
+        assert!(requests[1].starts_with("POST /0/private/OpenOrders"));
+        assert!(requests[1].ends_with("userref=7"));
+    }
+
+    #[tokio::test]
+    async fn charges_orders_and_cancellations_to_the_pair() {
+        let placed = r#"{"error":[],"result":{"descr":{"order":"buy 1.00000000 XBTUSD @ market"},"txid":["OQCLML-BW3P3-BUCMWZ"]}}"#;
+        let cancelled = r#"{"error":[],"result":{"count":1}}"#;
+        let (url, _) = serve(vec![(200, placed), (200, cancelled)]).await;
+        let client = client(url, fast_retries());
+        client.add_order(&order(None)).await.unwrap();
+        client
+            .cancel_order("OQCLML-BW3P3-BUCMWZ".to_owned())
+            .await
+            .unwrap();
+        // 1 for placing the order and 8 for cancelling it within 5 seconds.
+        let remaining = client.remaining_trading_limit("XBTUSD");
+        assert!((171.0..172.0).contains(&remaining));
+        assert_eq!(client.remaining_trading_limit("ETHUSD"), 180.0);
+    }
+
+    #[tokio::test]
+    async fn middleware_sees_every_attempt() {
+        let (url, _) = serve(vec![
+            (502, ""),
+            (200, r#"{"error":["EGeneral:Invalid arguments"]}"#),
+        ])
+        .await;
+        let recorder = Arc::new(Recorder::default());
+        let creds = KrakenCredentials::new("key".to_owned(), PRIVATE_KEY.to_owned());
+        let client = Client::builder(AccountTier::Pro)
+            .base_url(url)
+            .retry_policy(fast_retries())
+            .middleware(recorder.clone())
+            .credentials(creds)
+            .build()
+            .unwrap();
+        let _ = client.account_balance().await;
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                r#"/0/private/Balance true "[redacted]" Some(502) []"#,
+                r#"/0/private/Balance true "[redacted]" Some(200) ["EGeneral:Invalid arguments"]"#,
+            ]
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn traces_requests_without_secrets() {
+        let balance = r#"{"error":[],"result":{"ZUSD":"1.0"}}"#;
+        let (url, _) = serve(vec![(503, ""), (200, balance)]).await;
+        let creds = KrakenCredentials::new("my-api-key".to_owned(), PRIVATE_KEY.to_owned());
+        tracing::debug!(?creds);
+        let client = Client::builder(AccountTier::Pro)
+            .base_url(url)
+            .retry_policy(fast_retries())
+            .credentials(creds)
+            .build()
+            .unwrap();
+        client.account_balance().await.unwrap();
+        assert!(logs_contain("endpoint=\"/private/Balance\""));
+        assert!(logs_contain("retrying after a transient failure"));
+        assert!(logs_contain("status=Some(200)"));
+        assert!(!logs_contain("my-api-key"));
+        assert!(!logs_contain(PRIVATE_KEY));
+    }
+
+    #[tokio::test]
+    async fn calls_endpoints_without_bindings() {
+        let depth = r#"{"error":[],"result":{"XXBTZUSD":{"asks":[],"bids":[]}}}"#;
+        let ledgers = r#"{"error":[],"result":{"ledger":{},"count":0}}"#;
+        let (url, requests) = serve(vec![(200, depth), (200, ledgers), (200, ledgers)]).await;
+        let client = client(url, fast_retries());
+        let resp: serde_json::Value = client
+            .public_request("/public/Depth", [("pair", "XBTUSD"), ("count", "2")])
+            .await
+            .unwrap();
+        assert_eq!(resp["result"]["XXBTZUSD"]["asks"], json!([]));
+        let resp: serde_json::Value = client
+            .private_request("/private/Ledgers", json!({"asset": "XBT"}))
+            .await
+            .unwrap();
+        assert_eq!(resp["result"]["count"], 0);
+        let _: serde_json::Value = client
+            .private_request("/private/Ledgers", ())
+            .await
+            .unwrap();
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /0/public/Depth?pair=XBTUSD&count=2 "));
+        assert!(requests[1].starts_with("POST /0/private/Ledgers"));
+        assert!(requests[1].contains("nonce="));
+        assert!(requests[1].ends_with("&asset=XBT"));
+        // `()` adds no parameters besides the nonce.
+        let (_, body) = requests[2].rsplit_once(' ').unwrap();
+        assert!(body.starts_with("nonce=") && !body.contains('&'));
+    }
+
+    #[tokio::test]
+    async fn keys_asset_info_by_normalized_asset() {
+        let assets = r#"{"error":[],"result":{
+            "XXBT":{"aclass":"currency","altname":"XBT","decimals":10,"display_decimals":5,"collateral_value":1.0,"status":"enabled"},
+            "DOT.S":{"aclass":"currency","altname":"DOT.S","decimals":10,"display_decimals":8,"status":"deposit_only"}
+        }}"#;
+        let (url, requests) = serve(vec![(200, assets)]).await;
+        let assets = vec![Asset::from_kraken("BTC"), Asset::from_kraken("DOT.S")];
+        let info = client(url, fast_retries())
+            .asset_info(assets, None)
+            .await
+            .unwrap();
+        let btc = &info[&Asset::from_kraken("BTC")];
+        assert_eq!(btc.collateral_value, Some(1.0));
+        assert_eq!(btc.status, Some(AssetStatus::Enabled));
+        let staked = &info[&Asset::from_kraken("DOT.S")];
+        assert_eq!(staked.status, Some(AssetStatus::DepositOnly));
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /0/public/Assets?asset=XXBT%2CDOT.S "));
+    }
+
+    #[tokio::test]
+    async fn fetches_tickers_for_several_pairs() {
+        let tickers = r#"{"error":[],"result":{
+            "XXBTZUSD":{"a":["30000.1","1","1.000"],"b":["30000.0","2","2.000"],"c":["30000.1","0.1"],"v":["100.1","200.2"],"p":["29950.5","29900.2"],"t":[1000,2000],"l":["29500.0","29400.0"],"h":["30100.0","30200.0"],"o":"29800.0"},
+            "XETHZUSD":{"a":["2000.1","1","1.000"],"b":["2000.0","2","2.000"],"c":["2000.1","0.1"],"v":["10.1","20.2"],"p":["1990.5","1980.2"],"t":[100,200],"l":["1950.0","1940.0"],"h":["2010.0","2020.0"],"o":"1999.5"}
+        }}"#;
+        let (url, requests) = serve(vec![(200, tickers)]).await;
+        let pairs = vec!["XBTUSD".parse().unwrap(), "ETHUSD".parse().unwrap()];
+        let resp = client(url, fast_retries()).ticker(pairs).await.unwrap();
+        let result = resp.result.unwrap();
+        assert_eq!(result.len(), 2);
+        let eth = &result[&"XETHZUSD".parse().unwrap()];
+        assert_eq!(eth.opening_price.to_string(), "1999.5");
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /0/public/Ticker?pair=XBTUSD%2CETHUSD "));
+    }
+
+    #[tokio::test]
+    async fn skips_tickers_which_cant_be_parsed() {
+        let tickers = r#"{"error":[],"result":{
+            "XXBTZUSD":{"a":["30000.1","1","1.000"],"b":["30000.0","2","2.000"],"c":["30000.1","0.1"],"v":["100.1","200.2"],"p":["29950.5","29900.2"],"t":[1000,2000],"l":["29500.0","29400.0"],"h":["30100.0","30200.0"],"o":"29800.0"},
+            "XETHZUSD":{"a":["2000.1","1","1.000"],"b":["2000.0","2","2.000"],"c":["2000.1","0.1"],"v":["10.1","20.2"],"p":["1990.5","1980.2"],"t":[100,200],"l":["1950.0","1940.0"],"h":["2010.0","2020.0"],"o":"not a price"},
+            "XBT USD":{"a":["30000.1","1","1.000"],"b":["30000.0","2","2.000"],"c":["30000.1","0.1"],"v":["100.1","200.2"],"p":["29950.5","29900.2"],"t":[1000,2000],"l":["29500.0","29400.0"],"h":["30100.0","30200.0"],"o":"29800.0"}
+        }}"#;
+        let (url, _) = serve(vec![(200, tickers)]).await;
+        let resp = client(url, fast_retries()).ticker(vec![]).await.unwrap();
+        let result = resp.result.unwrap();
+        let names: Vec<_> = result.keys().map(AssetPair::as_str).collect();
+        assert_eq!(names, vec!["XXBTZUSD"]);
+        let mut skipped: Vec<_> = resp.skipped.keys().map(String::as_str).collect();
+        skipped.sort();
+        assert_eq!(skipped, vec!["XBT USD", "XETHZUSD"]);
     }
 }
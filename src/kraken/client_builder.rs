@@ -0,0 +1,319 @@
+use crate::kraken::authentication::{Authenticated, Public};
+use crate::kraken::endpoints::{DEFAULT_API_VERSION, DEFAULT_BASE_URL};
+use crate::kraken::futures::FuturesClient;
+use crate::kraken::middleware::Middleware;
+use crate::kraken::nonce::{AtomicNonce, NonceProvider};
+use crate::kraken::ratelimiter::{LeakyBucket, RateLimiter};
+use crate::kraken::retry_policy::RetryPolicy;
+use crate::kraken::transport::Transport;
+use crate::kraken::{AccountTier, Client, KrakenCredentials};
+use reqwest::header::HeaderMap;
+use reqwest::{Proxy, Url};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// ClientBuilder configures a Client. Every option has a default
+/// matching Client::new, so only the options that differ need to be set.
+/// The builder produces a PublicClient until credentials are given to it,
+/// after which it produces a Client that can call private endpoints.
+pub struct ClientBuilder<A = Public> {
+    pub(crate) auth: A,
+    pub(crate) tier: AccountTier,
+    pub(crate) base_url: Url,
+    pub(crate) api_version: String,
+    pub(crate) nonces: Arc<dyn NonceProvider>,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) rate_limiter: Arc<dyn RateLimiter>,
+    pub(crate) middleware: Vec<Arc<dyn Middleware>>,
+    http_client: Option<reqwest::Client>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    proxies: Vec<Proxy>,
+    user_agent: String,
+    default_headers: HeaderMap,
+    pool_idle_timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+}
+
+impl ClientBuilder<Public> {
+    pub fn new(tier: AccountTier) -> Self {
+        Self {
+            auth: Public,
+            tier,
+            base_url: Url::parse(DEFAULT_BASE_URL).unwrap(),
+            api_version: DEFAULT_API_VERSION.to_owned(),
+            nonces: Arc::new(AtomicNonce::new()),
+            retry: RetryPolicy::default(),
+            rate_limiter: Arc::new(LeakyBucket::new(tier)),
+            middleware: Vec::new(),
+            http_client: None,
+            connect_timeout: None,
+            timeout: None,
+            proxies: Vec::new(),
+            user_agent: DEFAULT_USER_AGENT.to_owned(),
+            default_headers: HeaderMap::new(),
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+        }
+    }
+
+    /// Sets the credentials used to sign requests to private endpoints.
+    pub fn credentials(self, creds: KrakenCredentials) -> ClientBuilder<Authenticated> {
+        ClientBuilder {
+            auth: Authenticated { creds },
+            tier: self.tier,
+            base_url: self.base_url,
+            api_version: self.api_version,
+            nonces: self.nonces,
+            retry: self.retry,
+            rate_limiter: self.rate_limiter,
+            middleware: self.middleware,
+            http_client: self.http_client,
+            connect_timeout: self.connect_timeout,
+            timeout: self.timeout,
+            proxies: self.proxies,
+            user_agent: self.user_agent,
+            default_headers: self.default_headers,
+            pool_idle_timeout: self.pool_idle_timeout,
+            pool_max_idle_per_host: self.pool_max_idle_per_host,
+        }
+    }
+}
+
+impl ClientBuilder<Authenticated> {
+    /// Builds a FuturesClient, for a builder made by FuturesClient::builder.
+    pub fn build_futures(mut self) -> Result<FuturesClient, reqwest::Error> {
+        let transport = self.transport()?;
+        Ok(FuturesClient::from_builder(self, transport))
+    }
+}
+
+impl<A> ClientBuilder<A> {
+    /// Sets the URL requests are sent to instead of `https://api.kraken.com`,
+    /// such as a local mock server or a recording proxy. Any path on the
+    /// base URL is kept, and the API version and endpoint are appended to it.
+    pub fn base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Sets the API version path segment, which defaults to "0".
+    pub fn api_version(mut self, api_version: &str) -> Self {
+        self.api_version = api_version.to_owned();
+        self
+    }
+
+    /// Sets the NonceProvider used for private requests, which defaults to
+    /// an AtomicNonce. Use a FileNonce when several processes share an API
+    /// key, or an `Arc` around one provider to share it between clients.
+    pub fn nonce_provider<P: NonceProvider + 'static>(mut self, provider: P) -> Self {
+        self.nonces = Arc::new(provider);
+        self
+    }
+
+    /// Sets when failed requests are retried. Defaults to
+    /// RetryPolicy::default; use RetryPolicy::none to disable retries.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Sets the RateLimiter requests wait on, which defaults to a
+    /// LeakyBucket for the account tier. Pass an `Arc` to share one
+    /// limiter between clients, or a FileRateLimiter to share one
+    /// between processes.
+    pub fn rate_limiter<R: RateLimiter + 'static>(mut self, limiter: R) -> Self {
+        self.rate_limiter = Arc::new(limiter);
+        self
+    }
+
+    /// Adds a Middleware which observes every request and response.
+    /// May be called more than once; middleware run in the order added.
+    pub fn middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Uses a pre-built reqwest::Client instead of building one.
+    /// The other HTTP options on this builder are ignored when it is set.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Sets the timeout for establishing a connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for a whole request, from connecting
+    /// until the response body has been read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Routes requests through an HTTP or HTTPS proxy.
+    /// May be called more than once to add several proxies.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Sets the User-Agent header, which defaults to
+    /// this crate's name and version.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_owned();
+        self
+    }
+
+    /// Sets headers sent with every request.
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Sets how long an idle pooled connection is kept open.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept open.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Builds the Client. Fails if the underlying reqwest::Client
+    /// cannot be built, such as when the TLS backend fails to initialize.
+    pub fn build(mut self) -> Result<Client<A>, reqwest::Error> {
+        let transport = self.transport()?;
+        Ok(Client::from_builder(self, transport))
+    }
+
+    // Builds what sends the requests, leaving the
+    // options describing the API on the builder.
+    pub(crate) fn transport(&mut self) -> Result<Transport, reqwest::Error> {
+        let http = match self.http_client.take() {
+            Some(client) => client,
+            None => self.build_http_client()?,
+        };
+        Ok(Transport {
+            http,
+            rate_limiter: self.rate_limiter.clone(),
+            nonces: self.nonces.clone(),
+            retry: self.retry,
+            middleware: std::mem::take(&mut self.middleware),
+        })
+    }
+
+    fn build_http_client(&mut self) -> Result<reqwest::Client, reqwest::Error> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(self.user_agent.as_str())
+            .default_headers(std::mem::take(&mut self.default_headers));
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        for proxy in self.proxies.drain(..) {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClientBuilder;
+    use crate::kraken::test_server;
+    use crate::kraken::{
+        AccountTier, Authenticated, Client, ClientError, KrakenCredentials, LeakyBucket, Public,
+        RateLimiter, RetryPolicy,
+    };
+    use pretty_assertions::assert_eq;
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::{Proxy, Url};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    const SERVER_TIME: &str = r#"{"error":[],"result":{"unixtime":1688669448,"rfc1123":"Thu, 06 Jul 23 18:50:48 +0000"}}"#;
+
+    fn builder(url: &Url) -> ClientBuilder {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Trace", HeaderValue::from_static("on"));
+        ClientBuilder::new(AccountTier::Starter)
+            .base_url(url.join("kraken/").unwrap())
+            .retry_policy(RetryPolicy::none())
+            .connect_timeout(Duration::from_secs(2))
+            .timeout(Duration::from_millis(500))
+            .proxy(Proxy::https("http://localhost:3128").unwrap())
+            .user_agent("my-bot/1.0")
+            .default_headers(headers)
+            .pool_idle_timeout(Duration::from_secs(30))
+            .pool_max_idle_per_host(4)
+    }
+
+    #[tokio::test]
+    async fn sends_requests_with_http_options() {
+        let (url, mut requests) =
+            test_server::serve(Duration::ZERO, vec![(200, SERVER_TIME)]).await;
+        let client = builder(&url).build().unwrap();
+        let resp = client.server_time().await.unwrap();
+        assert_eq!(resp.result.unwrap().unix_time, 1688669448);
+        let req = requests.recv().await.unwrap();
+        assert_eq!(req.target, "/kraken/0/public/Time");
+        assert_eq!(req.header("User-Agent"), Some("my-bot/1.0"));
+        assert_eq!(req.header("X-Trace"), Some("on"));
+    }
+
+    #[tokio::test]
+    async fn times_out_slow_responses() {
+        let slow = Duration::from_secs(5);
+        let (url, mut requests) = test_server::serve(slow, vec![(200, SERVER_TIME)]).await;
+        let client = builder(&url).build().unwrap();
+        let started = Instant::now();
+        let result = client.server_time().await;
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(matches!(result, Err(ClientError::Http(err)) if err.is_timeout()));
+        requests.recv().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn credentials_switch_to_authenticated_client() {
+        let _client: Client<Public> = ClientBuilder::new(AccountTier::Starter).build().unwrap();
+        let creds = KrakenCredentials::new("key".to_owned(), "c2VjcmV0".to_owned());
+        let _client: Client<Authenticated> = ClientBuilder::new(AccountTier::Starter)
+            .timeout(Duration::from_secs(10))
+            .credentials(creds)
+            .build()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn clients_share_a_rate_limiter() {
+        let shared = Arc::new(LeakyBucket::new(AccountTier::Starter));
+        let first = ClientBuilder::new(AccountTier::Starter)
+            .rate_limiter(shared.clone())
+            .build()
+            .unwrap();
+        let second = ClientBuilder::new(AccountTier::Starter)
+            .rate_limiter(shared.clone())
+            .build()
+            .unwrap();
+        shared.use_rate_limit(5).await;
+        assert_eq!(first.remaining_rate_limit().await, 10);
+        assert_eq!(second.remaining_rate_limit().await, 10);
+    }
+}
@@ -0,0 +1,35 @@
+use std::{fmt, io};
+
+/// ClientError is returned by Client methods which can fail
+/// for reasons other than the HTTP request itself.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request could not be sent, or the response could not be read.
+    Http(reqwest::Error),
+    /// The response body was not the JSON the endpoint was expected to return.
+    Decode(serde_json::Error),
+    /// The NonceProvider failed to produce a nonce.
+    Nonce(io::Error),
+    /// Kraken returned errors instead of a result,
+    /// such as "EQuery:Unknown asset pair".
+    Kraken(Vec<String>),
+}
+
+impl std::error::Error for ClientError {}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(err) => write!(f, "{}", err),
+            Self::Decode(err) => write!(f, "Failed to decode the response: {}", err),
+            Self::Nonce(err) => write!(f, "Failed to generate a nonce: {}", err),
+            Self::Kraken(errors) => write!(f, "Kraken returned errors: {}", errors.join(", ")),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Http(err)
+    }
+}
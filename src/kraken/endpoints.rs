@@ -1,15 +1,118 @@
 use reqwest::Url;
 
-pub const ACCOUNT_BALANCE: &str = concat!("https://api.kraken.com/0", "/private/Balance");
-pub const TRADE_BALANCE: &str = concat!("https://api.kraken.com/0", "/private/TradeBalance");
-pub const OPEN_ORDERS: &str = concat!("https://api.kraken.com/0", "/private/OpenOrders");
-pub const ASSET_INFO: &str = concat!("https://api.kraken.com/0", "/public/Assets");
-pub const SYSTEM_STATUS: &str = concat!("https://api.kraken.com/0", "/public/SystemStatus");
-pub const SYSTEM_TIME: &str = concat!("https://api.kraken.com/0", "/public/Time");
-pub const TICKER: &str = concat!("https://api.kraken.com/0", "/public/Ticker");
-pub const RECENT_SPREADS: &str = concat!("https://api.kraken.com/0", "/public/Spread");
-pub const ASSET_PAIRS: &str = concat!("https://api.kraken.com/0", "/public/AssetPairs");
+pub const DEFAULT_BASE_URL: &str = "https://api.kraken.com";
+pub const DEFAULT_API_VERSION: &str = "0";
 
+pub const ACCOUNT_BALANCE: &str = "/private/Balance";
+pub const TRADE_BALANCE: &str = "/private/TradeBalance";
+pub const OPEN_ORDERS: &str = "/private/OpenOrders";
+pub const CLOSED_ORDERS: &str = "/private/ClosedOrders";
+pub const ADD_ORDER: &str = "/private/AddOrder";
+pub const CANCEL_ORDER: &str = "/private/CancelOrder";
+pub const LEDGERS: &str = "/private/Ledgers";
+pub const QUERY_LEDGERS: &str = "/private/QueryLedgers";
+pub const TRADES_HISTORY: &str = "/private/TradesHistory";
+pub const QUERY_TRADES: &str = "/private/QueryTrades";
+pub const ASSET_INFO: &str = "/public/Assets";
+pub const SYSTEM_STATUS: &str = "/public/SystemStatus";
+pub const SYSTEM_TIME: &str = "/public/Time";
+pub const TICKER: &str = "/public/Ticker";
+pub const RECENT_SPREADS: &str = "/public/Spread";
+pub const ASSET_PAIRS: &str = "/public/AssetPairs";
+
+pub const WEBSOCKET_PUBLIC: &str = "wss://ws.kraken.com";
+pub const WEBSOCKET_PRIVATE: &str = "wss://ws-auth.kraken.com";
+
+/// ApiUrl composes the URL of each endpoint from a configurable base URL,
+/// so requests can be sent to a mock server or a proxy instead of Kraken.
+#[derive(Clone, Debug)]
+pub struct ApiUrl {
+    base: Url,
+    // The path between the base URL and the endpoint, e.g. "/0".
+    prefix: String,
+    // The part of the prefix Kraken includes when verifying signatures.
+    signed_prefix: String,
+}
+
+impl ApiUrl {
+    pub fn new(base: Url, prefix: String, signed_prefix: String) -> Self {
+        Self {
+            base,
+            prefix,
+            signed_prefix,
+        }
+    }
+
+    /// Builds the ApiUrl for the spot API, where
+    /// every path is prefixed with the API version.
+    pub fn spot(base: Url, api_version: &str) -> Self {
+        let prefix = format!("/{}", api_version.trim_matches('/'));
+        Self::new(base, prefix.clone(), prefix)
+    }
+
+    pub fn endpoint(&self, path: &str) -> Url {
+        let mut url = self.base.clone();
+        let base_path = url.path().trim_end_matches('/').to_owned();
+        url.set_path(&format!("{}{}{}", base_path, self.prefix, path));
+        url
+    }
+
+    /// Returns the path Kraken expects in the request signature.
+    /// It doesn't depend on the base URL, so a proxy which forwards
+    /// requests under a different path still produces valid signatures.
+    pub fn signed_path(&self, path: &str) -> String {
+        format!("{}{}", self.signed_prefix, path)
+    }
+}
+
+impl Default for ApiUrl {
+    fn default() -> Self {
+        Self::spot(Url::parse(DEFAULT_BASE_URL).unwrap(), DEFAULT_API_VERSION)
+    }
+}
+
+/// Parses a full endpoint URL. Endpoint constants are now paths, such
+/// as TICKER, which are resolved against the default base URL.
+#[deprecated(
+    since = "0.2.0",
+    note = "endpoints are paths now; set the base URL with ClientBuilder::base_url"
+)]
 pub fn endpoint(name: &str) -> Url {
-    Url::parse(name).unwrap()
+    Url::parse(name).unwrap_or_else(|_| ApiUrl::default().endpoint(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiUrl, ACCOUNT_BALANCE, TICKER};
+    use pretty_assertions::assert_eq;
+    use reqwest::Url;
+
+    #[test]
+    fn composes_default_endpoints() {
+        let api = ApiUrl::default();
+        let url = api.endpoint(TICKER);
+        assert_eq!(url.as_str(), "https://api.kraken.com/0/public/Ticker");
+        assert_eq!(api.signed_path(ACCOUNT_BALANCE), "/0/private/Balance");
+    }
+
+    #[test]
+    fn signs_the_same_path_behind_a_proxy() {
+        let base = Url::parse("http://localhost:8080/kraken/").unwrap();
+        let api = ApiUrl::spot(base, "0");
+        let url = api.endpoint(ACCOUNT_BALANCE);
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8080/kraken/0/private/Balance"
+        );
+        assert_eq!(api.signed_path(ACCOUNT_BALANCE), "/0/private/Balance");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn endpoint_accepts_urls_and_paths() {
+        let url = super::endpoint("https://api.kraken.com/0/public/Time");
+        assert_eq!(url.as_str(), "https://api.kraken.com/0/public/Time");
+        let url = super::endpoint(TICKER);
+        assert_eq!(url.as_str(), "https://api.kraken.com/0/public/Ticker");
+    }
 }
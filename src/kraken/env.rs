@@ -1,3 +1,5 @@
+use std::fmt;
+
 const KRAKEN_API_KEY: &str = "KRAKEN_API_KEY";
 const KRAKEN_PRIVATE_KEY: &str = "KRAKEN_PRIVATE_KEY";
 
@@ -30,3 +32,13 @@ impl KrakenCredentials {
         &self.private_key
     }
 }
+
+// Keeps the credentials out of logs and panic messages.
+impl fmt::Debug for KrakenCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KrakenCredentials")
+            .field("api_key", &"[redacted]")
+            .field("private_key", &"[redacted]")
+            .finish()
+    }
+}
@@ -0,0 +1,374 @@
+use super::endpoints::{
+    endpoint_cost, futures_api_url, ACCOUNTS, CANCEL_ORDER, DEMO_BASE_URL, EDIT_ORDER, FILLS,
+    HISTORY, INSTRUMENTS, OPEN_POSITIONS, ORDER_BOOK, PRODUCTION_BASE_URL, SEND_ORDER, TICKERS,
+};
+use super::payload::{
+    AccountsResponse, CancelOrderInput, CancelOrderResponse, EditOrderInput, EditOrderResponse,
+    FillsInput, FillsResponse, HistoryInput, HistoryResponse, InstrumentsResponse,
+    OpenPositionsResponse, OrderBookInput, OrderBookResponse, SendOrderInput, SendOrderResponse,
+    SerializableEditOrderInput, SerializableSendOrderInput, TickersResponse,
+};
+use crate::kraken::endpoints::ApiUrl;
+use crate::kraken::env::KrakenCredentials;
+use crate::kraken::request_builder::{ParamEncoding, PrivacyLevel, RequestBuilder};
+use crate::kraken::transport::{Retry, Transport};
+use crate::kraken::{AccountTier, Authenticated, ClientBuilder, ClientError, LeakyBucket};
+use reqwest::{Method, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// Fetching fills before a given time costs more than the latest fills.
+const FILLS_BEFORE_COST: usize = 25;
+
+/// FuturesClient talks to the Kraken Futures REST API, which is separate
+/// from the spot API and requires its own API keys. It is configured with
+/// the same ClientBuilder as the spot Client, and shares its rate limiting,
+/// retries, nonces and middleware.
+pub struct FuturesClient {
+    transport: Transport,
+    creds: KrakenCredentials,
+    api_url: ApiUrl,
+}
+
+impl FuturesClient {
+    pub fn new(creds: KrakenCredentials) -> Self {
+        Self::builder(creds)
+            .build_futures()
+            .expect("FuturesClient::new()")
+    }
+
+    /// Connects to the demo environment at `demo-futures.kraken.com`,
+    /// which requires separate demo API keys.
+    pub fn demo(creds: KrakenCredentials) -> Self {
+        Self::with_base_url(creds, Url::parse(DEMO_BASE_URL).unwrap())
+    }
+
+    /// Sends requests to the given base URL, such as a local mock server.
+    /// Endpoints are appended under `/derivatives/api/v3`.
+    pub fn with_base_url(creds: KrakenCredentials, base_url: Url) -> Self {
+        Self::builder(creds)
+            .base_url(base_url)
+            .build_futures()
+            .expect("FuturesClient::with_base_url()")
+    }
+
+    /// Returns a ClientBuilder set up for Kraken Futures: requests go to
+    /// `futures.kraken.com` and wait on LeakyBucket::futures. Finish it
+    /// with ClientBuilder::build_futures. The API version is ignored.
+    pub fn builder(creds: KrakenCredentials) -> ClientBuilder<Authenticated> {
+        ClientBuilder::new(AccountTier::Starter)
+            .base_url(Url::parse(PRODUCTION_BASE_URL).unwrap())
+            .rate_limiter(LeakyBucket::futures())
+            .credentials(creds)
+    }
+
+    pub(crate) fn from_builder(
+        builder: ClientBuilder<Authenticated>,
+        transport: Transport,
+    ) -> Self {
+        Self {
+            transport,
+            creds: builder.auth.creds,
+            api_url: futures_api_url(builder.base_url),
+        }
+    }
+
+    /// Returns how much of the budget for private endpoints is left.
+    /// Compare it against futures::endpoint_cost to see whether a call
+    /// would have to wait.
+    pub async fn remaining_rate_limit(&self) -> usize {
+        self.transport.rate_limiter.remaining().await
+    }
+
+    // Sends a request to a public endpoint and decodes the response.
+    async fn public<P, R>(&self, path: &str, params: Option<P>) -> Result<R, ClientError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let cost = endpoint_cost(path);
+        let body = self
+            .transport
+            .send(path, cost, Retry::Idempotent, false, |path, _| {
+                Ok(RequestBuilder {
+                    method: Method::GET,
+                    url: self.api_url.endpoint(path),
+                    params: params.as_ref(),
+                    param_encoding: ParamEncoding::QueryEncoded,
+                    privacy_level: PrivacyLevel::Public,
+                })
+            })
+            .await?;
+        serde_json::from_slice(&body).map_err(ClientError::Decode)
+    }
+
+    // Sends a signed request to a private endpoint and decodes the
+    // response. GET requests send `params` in the query string,
+    // and anything else sends them as a form.
+    async fn private<P, R>(
+        &self,
+        method: Method,
+        path: &str,
+        cost: usize,
+        retry: Retry,
+        params: Option<P>,
+    ) -> Result<R, ClientError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let param_encoding = match method {
+            Method::GET => ParamEncoding::QueryEncoded,
+            _ => ParamEncoding::FormEncoded,
+        };
+        let body = self
+            .transport
+            .send(path, cost, retry, true, |path, nonce| {
+                Ok(RequestBuilder {
+                    method: method.clone(),
+                    url: self.api_url.endpoint(path),
+                    params: params.as_ref(),
+                    param_encoding,
+                    privacy_level: PrivacyLevel::FuturesPrivate {
+                        // Signed attempts always have a nonce.
+                        nonce: nonce.unwrap_or_default(),
+                        api_key: self.creds.api_key().clone(),
+                        private_key: self.creds.private_key().clone(),
+                        uri_path: self.api_url.signed_path(path),
+                    },
+                })
+            })
+            .await?;
+        serde_json::from_slice(&body).map_err(ClientError::Decode)
+    }
+
+    pub async fn instruments(&self) -> Result<InstrumentsResponse, ClientError> {
+        self.public::<(), _>(INSTRUMENTS, None).await
+    }
+
+    pub async fn tickers(&self) -> Result<TickersResponse, ClientError> {
+        self.public::<(), _>(TICKERS, None).await
+    }
+
+    pub async fn order_book(&self, symbol: String) -> Result<OrderBookResponse, ClientError> {
+        self.public(ORDER_BOOK, Some(OrderBookInput { symbol }))
+            .await
+    }
+
+    /// Returns the most recent trades for the symbol,
+    /// or those before `last_time` if it is provided.
+    pub async fn history(
+        &self,
+        symbol: String,
+        last_time: Option<String>,
+    ) -> Result<HistoryResponse, ClientError> {
+        self.public(HISTORY, Some(HistoryInput { symbol, last_time }))
+            .await
+    }
+
+    pub async fn accounts(&self) -> Result<AccountsResponse, ClientError> {
+        let cost = endpoint_cost(ACCOUNTS);
+        self.private::<(), _>(Method::GET, ACCOUNTS, cost, Retry::Idempotent, None)
+            .await
+    }
+
+    pub async fn open_positions(&self) -> Result<OpenPositionsResponse, ClientError> {
+        let cost = endpoint_cost(OPEN_POSITIONS);
+        self.private::<(), _>(Method::GET, OPEN_POSITIONS, cost, Retry::Idempotent, None)
+            .await
+    }
+
+    /// Places an order. The request is not retried after a transient
+    /// failure, since the order may have been placed even though no
+    /// response arrived.
+    pub async fn send_order(
+        &self,
+        order: SendOrderInput,
+    ) -> Result<SendOrderResponse, ClientError> {
+        let params = SerializableSendOrderInput::from(&order);
+        let cost = endpoint_cost(SEND_ORDER);
+        self.private(Method::POST, SEND_ORDER, cost, Retry::Never, Some(params))
+            .await
+    }
+
+    pub async fn edit_order(&self, edit: EditOrderInput) -> Result<EditOrderResponse, ClientError> {
+        let params = SerializableEditOrderInput::from(&edit);
+        let cost = endpoint_cost(EDIT_ORDER);
+        self.private(Method::POST, EDIT_ORDER, cost, Retry::Never, Some(params))
+            .await
+    }
+
+    pub async fn cancel_order(
+        &self,
+        cancel: CancelOrderInput,
+    ) -> Result<CancelOrderResponse, ClientError> {
+        let cost = endpoint_cost(CANCEL_ORDER);
+        self.private(
+            Method::POST,
+            CANCEL_ORDER,
+            cost,
+            Retry::Idempotent,
+            Some(cancel),
+        )
+        .await
+    }
+
+    /// Returns the most recent fills, or those
+    /// before `last_fill_time` if it is provided.
+    pub async fn fills(
+        &self,
+        last_fill_time: Option<String>,
+    ) -> Result<FillsResponse, ClientError> {
+        let cost = match last_fill_time {
+            Some(_) => FILLS_BEFORE_COST,
+            None => endpoint_cost(FILLS),
+        };
+        let params = FillsInput { last_fill_time };
+        self.private(Method::GET, FILLS, cost, Retry::Idempotent, Some(params))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FuturesClient;
+    use crate::kraken::env::KrakenCredentials;
+    use crate::kraken::futures::{OrderType, SendOrderInput, Side};
+    use crate::kraken::middleware::{Middleware, RequestInfo, ResponseInfo};
+    use crate::kraken::test_server;
+    use crate::kraken::{ClientError, RetryPolicy};
+    use bigdecimal::BigDecimal;
+    use pretty_assertions::assert_eq;
+    use reqwest::Url;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const PRIVATE_KEY: &str =
+        "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==";
+
+    const TICKERS: &str = r#"{"result":"success","tickers":[{"symbol":"PF_XBTUSD","markPrice":9400.1,"suspended":false}]}"#;
+
+    // Records the path, privacy and status of every response.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Middleware for Recorder {
+        fn after_response(&self, req: &RequestInfo, resp: &ResponseInfo) {
+            let status = resp.status.map(|status| status.as_u16());
+            let line = format!("{} {} {:?}", req.path, req.private, status);
+            self.0.lock().unwrap().push(line);
+        }
+    }
+
+    fn client(url: Url) -> FuturesClient {
+        let creds = KrakenCredentials::new("key".to_owned(), PRIVATE_KEY.to_owned());
+        FuturesClient::builder(creds)
+            .base_url(url)
+            .user_agent("my-bot/1.0")
+            .retry_policy(RetryPolicy {
+                initial_delay: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            })
+            .build_futures()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sends_public_requests() {
+        let (url, mut requests) = test_server::serve(Duration::ZERO, vec![(200, TICKERS)]).await;
+        let resp = client(url).tickers().await.unwrap();
+        let ticker = &resp.tickers.unwrap()[0];
+        assert_eq!(ticker.symbol, "PF_XBTUSD");
+        assert_eq!(
+            ticker.mark_price,
+            Some(BigDecimal::from_str("9400.1").unwrap())
+        );
+        let req = requests.recv().await.unwrap();
+        assert_eq!(req.method, "GET");
+        assert_eq!(req.target, "/derivatives/api/v3/tickers");
+        assert_eq!(req.header("User-Agent"), Some("my-bot/1.0"));
+        assert_eq!(req.header("APIKey"), None);
+    }
+
+    #[tokio::test]
+    async fn signs_private_requests() {
+        let sent = r#"{"result":"success","sendStatus":{"order_id":"abc","status":"placed"}}"#;
+        let (url, mut requests) = test_server::serve(Duration::ZERO, vec![(200, sent)]).await;
+        let order = SendOrderInput {
+            order_type: OrderType::Lmt,
+            symbol: "PF_XBTUSD".to_owned(),
+            side: Side::Buy,
+            size: BigDecimal::from(1),
+            limit_price: Some(BigDecimal::from_str("9400.1").unwrap()),
+            stop_price: None,
+            cli_ord_id: None,
+            trigger_signal: None,
+            reduce_only: None,
+        };
+        let resp = client(url).send_order(order).await.unwrap();
+        assert_eq!(resp.send_status.unwrap().status, "placed");
+        let req = requests.recv().await.unwrap();
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.target, "/derivatives/api/v3/sendorder");
+        assert_eq!(
+            req.body,
+            "orderType=lmt&symbol=PF_XBTUSD&side=buy&size=1&limitPrice=9400.1"
+        );
+        assert_eq!(req.header("APIKey"), Some("key"));
+        assert!(req.header("Nonce").is_some());
+        assert!(req.header("Authent").is_some());
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures_through_middleware() {
+        let responses = vec![(502, ""), (200, TICKERS)];
+        let (url, mut requests) = test_server::serve(Duration::ZERO, responses).await;
+        let creds = KrakenCredentials::new("key".to_owned(), PRIVATE_KEY.to_owned());
+        let recorder = Arc::new(Recorder::default());
+        let client = FuturesClient::builder(creds)
+            .base_url(url)
+            .retry_policy(RetryPolicy {
+                initial_delay: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            })
+            .middleware(recorder.clone())
+            .build_futures()
+            .unwrap();
+        client.tickers().await.unwrap();
+        requests.recv().await.unwrap();
+        requests.recv().await.unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                "/derivatives/api/v3/tickers false Some(502)",
+                "/derivatives/api/v3/tickers false Some(200)",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_orders() {
+        let (url, _requests) = test_server::serve(Duration::ZERO, vec![(502, "")]).await;
+        let order = SendOrderInput {
+            order_type: OrderType::Mkt,
+            symbol: "PF_XBTUSD".to_owned(),
+            side: Side::Sell,
+            size: BigDecimal::from(1),
+            limit_price: None,
+            stop_price: None,
+            cli_ord_id: None,
+            trigger_signal: None,
+            reduce_only: None,
+        };
+        let result = client(url).send_order(order).await;
+        assert!(matches!(result, Err(ClientError::Decode(_))));
+    }
+
+    #[tokio::test]
+    async fn returns_decode_errors() {
+        let (url, _requests) = test_server::serve(Duration::ZERO, vec![(200, "{}")]).await;
+        let result = client(url).tickers().await;
+        assert!(matches!(result, Err(ClientError::Decode(_))));
+    }
+}
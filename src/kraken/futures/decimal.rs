@@ -0,0 +1,175 @@
+// Serde helpers for the decimals in futures responses and websocket messages.
+// Kraken Futures sends prices and sizes as JSON numbers, which are read back
+// through their shortest decimal form so that 9400.1 stays 9400.1, and
+// written as strings.
+use bigdecimal::BigDecimal;
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+struct Decimal(BigDecimal);
+
+struct AsString<'a>(&'a BigDecimal);
+
+impl Serialize for AsString<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+struct DecimalVisitor;
+
+impl Visitor<'_> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal number or string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+        BigDecimal::from_str(value)
+            .map(Decimal)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+        if !value.is_finite() {
+            return Err(E::invalid_value(de::Unexpected::Float(value), &self));
+        }
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+        Ok(Decimal(value.into()))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+        Ok(Decimal(value.into()))
+    }
+}
+
+pub fn serialize<S: Serializer>(value: &BigDecimal, serializer: S) -> Result<S::Ok, S::Error> {
+    AsString(value).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigDecimal, D::Error> {
+    Decimal::deserialize(deserializer).map(|Decimal(value)| value)
+}
+
+pub mod option {
+    use super::{AsString, Decimal};
+    use bigdecimal::BigDecimal;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<BigDecimal>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.as_ref().map(AsString).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<BigDecimal>, D::Error> {
+        let value = Option::<Decimal>::deserialize(deserializer)?;
+        Ok(value.map(|Decimal(value)| value))
+    }
+}
+
+/// Maps from a currency or name to an amount, which may be missing.
+pub mod map {
+    use super::{AsString, Decimal, HashMap};
+    use bigdecimal::BigDecimal;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<HashMap<String, BigDecimal>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(map) => serializer.collect_map(map.iter().map(|(k, v)| (k, AsString(v)))),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<HashMap<String, BigDecimal>>, D::Error> {
+        let value = Option::<HashMap<String, Decimal>>::deserialize(deserializer)?;
+        Ok(value.map(|map| map.into_iter().map(|(k, Decimal(v))| (k, v)).collect()))
+    }
+}
+
+/// One side of an order book, as (price, size) pairs.
+pub mod levels {
+    use super::{AsString, Decimal};
+    use bigdecimal::BigDecimal;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &[(BigDecimal, BigDecimal)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            value
+                .iter()
+                .map(|(price, size)| (AsString(price), AsString(size))),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(BigDecimal, BigDecimal)>, D::Error> {
+        let value = Vec::<(Decimal, Decimal)>::deserialize(deserializer)?;
+        Ok(value
+            .into_iter()
+            .map(|(Decimal(price), Decimal(size))| (price, size))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bigdecimal::BigDecimal;
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Level {
+        #[serde(with = "super")]
+        price: BigDecimal,
+        #[serde(default, with = "super::option")]
+        size: Option<BigDecimal>,
+    }
+
+    #[test]
+    fn reads_numbers_without_binary_noise() {
+        let level: Level = serde_json::from_value(json!({"price": 9400.1, "size": 0.3})).unwrap();
+        assert_eq!(level.price, BigDecimal::from_str("9400.1").unwrap());
+        assert_eq!(level.size, Some(BigDecimal::from_str("0.3").unwrap()));
+        let level: Level = serde_json::from_value(json!({"price": "12.50"})).unwrap();
+        assert_eq!(level.price, BigDecimal::from_str("12.50").unwrap());
+        assert_eq!(level.size, None);
+    }
+
+    #[test]
+    fn writes_strings() {
+        let level = Level {
+            price: BigDecimal::from_str("9400.1").unwrap(),
+            size: Some(BigDecimal::from(5)),
+        };
+        let json = serde_json::to_value(&level).unwrap();
+        assert_eq!(json, json!({"price": "9400.1", "size": "5"}));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::kraken::endpoints::ApiUrl;
+use reqwest::Url;
+
+pub const PRODUCTION_BASE_URL: &str = "https://futures.kraken.com";
+pub const DEMO_BASE_URL: &str = "https://demo-futures.kraken.com";
+
+pub const INSTRUMENTS: &str = "/instruments";
+pub const TICKERS: &str = "/tickers";
+pub const ORDER_BOOK: &str = "/orderbook";
+pub const HISTORY: &str = "/history";
+pub const ACCOUNTS: &str = "/accounts";
+pub const OPEN_POSITIONS: &str = "/openpositions";
+pub const SEND_ORDER: &str = "/sendorder";
+pub const EDIT_ORDER: &str = "/editorder";
+pub const CANCEL_ORDER: &str = "/cancelorder";
+pub const FILLS: &str = "/fills";
+
+pub const WEBSOCKET: &str = "wss://futures.kraken.com/ws/v1";
+pub const DEMO_WEBSOCKET: &str = "wss://demo-futures.kraken.com/ws/v1";
+
+/// Returns how much a call to the endpoint costs against Kraken Futures'
+/// budget for the private endpoints, which LeakyBucket::futures tracks.
+/// Orders cost 10, account queries 2, and public endpoints aren't
+/// counted. Fetching fills before a given time costs 25 rather than 2.
+pub fn endpoint_cost(path: &str) -> usize {
+    match path {
+        SEND_ORDER | EDIT_ORDER | CANCEL_ORDER => 10,
+        ACCOUNTS | OPEN_POSITIONS | FILLS => 2,
+        _ => 0,
+    }
+}
+
+// Kraken Futures serves the REST API under "/derivatives/api/v3",
+// but signs the endpoint path without the "/derivatives" prefix.
+pub fn futures_api_url(base: Url) -> ApiUrl {
+    ApiUrl::new(base, "/derivatives/api/v3".to_owned(), "/api/v3".to_owned())
+}
@@ -0,0 +1,10 @@
+pub use client::FuturesClient;
+pub use endpoints::endpoint_cost;
+pub use payload::*;
+pub use websocket::{FuturesFeed, FuturesMessage, FuturesSubscription, FuturesWebSocketClient};
+
+mod client;
+mod decimal;
+mod endpoints;
+mod payload;
+pub mod websocket;
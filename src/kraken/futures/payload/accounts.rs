@@ -0,0 +1,56 @@
+use crate::kraken::futures::decimal;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountsResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub accounts: Option<HashMap<String, Account>>,
+}
+
+/// Account covers the cash, margin and multi-collateral ("flex")
+/// accounts. Only the fields relevant to the account's type are set.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    #[serde(rename = "type")]
+    pub account_type: String,
+    pub currency: Option<String>,
+    #[serde(default, with = "decimal::map")]
+    pub balances: Option<HashMap<String, BigDecimal>>,
+    #[serde(default, with = "decimal::map")]
+    pub auxiliary: Option<HashMap<String, BigDecimal>>,
+    #[serde(default, with = "decimal::map")]
+    pub margin_requirements: Option<HashMap<String, BigDecimal>>,
+    #[serde(default, with = "decimal::map")]
+    pub trigger_estimates: Option<HashMap<String, BigDecimal>>,
+    pub currencies: Option<HashMap<String, FlexCurrency>>,
+    #[serde(default, with = "decimal::option")]
+    pub balance_value: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub portfolio_value: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub available_margin: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub initial_margin: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub maintenance_margin: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub pnl: Option<BigDecimal>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FlexCurrency {
+    #[serde(with = "decimal")]
+    pub quantity: BigDecimal,
+    #[serde(with = "decimal")]
+    pub value: BigDecimal,
+    #[serde(with = "decimal")]
+    pub collateral: BigDecimal,
+    #[serde(with = "decimal")]
+    pub available: BigDecimal,
+}
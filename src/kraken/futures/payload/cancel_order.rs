@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// CancelOrderInput identifies the order by either `order_id` or `cli_ord_id`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CancelOrderInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    #[serde(rename = "cliOrdId", skip_serializing_if = "Option::is_none")]
+    pub cli_ord_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOrderResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub cancel_status: Option<CancelStatus>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelStatus {
+    pub status: String,
+    pub order_id: Option<String>,
+    #[serde(rename = "cliOrdId")]
+    pub cli_ord_id: Option<String>,
+    #[serde(rename = "receivedTime")]
+    pub received_time: Option<String>,
+    #[serde(rename = "orderEvents")]
+    pub order_events: Option<Vec<Value>>,
+}
@@ -0,0 +1,61 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// EditOrderInput identifies the order by either `order_id`
+/// or `cli_ord_id`, and sets any of the fields to change.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EditOrderInput {
+    pub order_id: Option<String>,
+    pub cli_ord_id: Option<String>,
+    pub size: Option<BigDecimal>,
+    pub limit_price: Option<BigDecimal>,
+    pub stop_price: Option<BigDecimal>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SerializableEditOrderInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cli_ord_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_price: Option<String>,
+}
+
+impl From<&EditOrderInput> for SerializableEditOrderInput {
+    fn from(input: &EditOrderInput) -> Self {
+        Self {
+            order_id: input.order_id.clone(),
+            cli_ord_id: input.cli_ord_id.clone(),
+            size: input.size.as_ref().map(BigDecimal::to_string),
+            limit_price: input.limit_price.as_ref().map(BigDecimal::to_string),
+            stop_price: input.stop_price.as_ref().map(BigDecimal::to_string),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EditOrderResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub edit_status: Option<EditStatus>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EditStatus {
+    pub status: String,
+    pub order_id: Option<String>,
+    pub cli_ord_id: Option<String>,
+    pub received_time: Option<String>,
+    pub order_events: Option<Vec<Value>>,
+}
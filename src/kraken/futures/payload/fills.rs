@@ -0,0 +1,36 @@
+use crate::kraken::futures::decimal;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct FillsInput {
+    #[serde(rename = "lastFillTime", skip_serializing_if = "Option::is_none")]
+    pub last_fill_time: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FillsResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub fills: Option<Vec<Fill>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Fill {
+    pub fill_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub order_id: String,
+    #[serde(rename = "cliOrdId")]
+    pub cli_ord_id: Option<String>,
+    #[serde(with = "decimal")]
+    pub size: BigDecimal,
+    #[serde(with = "decimal")]
+    pub price: BigDecimal,
+    #[serde(rename = "fillTime")]
+    pub fill_time: String,
+    #[serde(rename = "fillType")]
+    pub fill_type: String,
+}
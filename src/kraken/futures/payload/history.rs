@@ -0,0 +1,33 @@
+use crate::kraken::futures::decimal;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct HistoryInput {
+    pub symbol: String,
+    #[serde(rename = "lastTime", skip_serializing_if = "Option::is_none")]
+    pub last_time: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub history: Option<Vec<Trade>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Trade {
+    pub time: String,
+    pub trade_id: u64,
+    #[serde(with = "decimal")]
+    pub price: BigDecimal,
+    #[serde(with = "decimal")]
+    pub size: BigDecimal,
+    pub side: String,
+    #[serde(rename = "type")]
+    pub trade_type: Option<String>,
+    pub uid: Option<String>,
+}
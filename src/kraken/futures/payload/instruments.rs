@@ -0,0 +1,52 @@
+use crate::kraken::futures::decimal;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentsResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub instruments: Option<Vec<Instrument>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Instrument {
+    pub symbol: String,
+    #[serde(rename = "type")]
+    pub instrument_type: String,
+    pub underlying: Option<String>,
+    pub tradeable: bool,
+    #[serde(default, with = "decimal::option")]
+    pub tick_size: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub contract_size: Option<BigDecimal>,
+    pub contract_value_trade_precision: Option<i64>,
+    #[serde(default, with = "decimal::option")]
+    pub impact_mid_size: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub max_position_size: Option<BigDecimal>,
+    pub opening_date: Option<String>,
+    pub last_trading_time: Option<String>,
+    pub margin_levels: Option<Vec<MarginLevel>>,
+    #[serde(default, with = "decimal::option")]
+    pub funding_rate_coefficient: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub max_relative_funding_rate: Option<BigDecimal>,
+    pub post_only: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginLevel {
+    #[serde(default, with = "decimal::option")]
+    pub contracts: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub num_non_contract_units: Option<BigDecimal>,
+    #[serde(with = "decimal")]
+    pub initial_margin: BigDecimal,
+    #[serde(with = "decimal")]
+    pub maintenance_margin: BigDecimal,
+}
@@ -0,0 +1,26 @@
+pub use accounts::{Account, AccountsResponse, FlexCurrency};
+pub use cancel_order::{CancelOrderInput, CancelOrderResponse, CancelStatus};
+pub use edit_order::{EditOrderInput, EditOrderResponse, EditStatus};
+pub use fills::{Fill, FillsInput, FillsResponse};
+pub use history::{HistoryInput, HistoryResponse, Trade};
+pub use instruments::{Instrument, InstrumentsResponse, MarginLevel};
+pub use open_positions::{OpenPosition, OpenPositionsResponse};
+pub use order::{OrderType, Side};
+pub use order_book::{OrderBook, OrderBookInput, OrderBookResponse};
+pub use send_order::{SendOrderInput, SendOrderResponse, SendStatus};
+pub use tickers::{FuturesTicker, TickersResponse};
+
+pub(crate) use edit_order::SerializableEditOrderInput;
+pub(crate) use send_order::SerializableSendOrderInput;
+
+mod accounts;
+mod cancel_order;
+mod edit_order;
+mod fills;
+mod history;
+mod instruments;
+mod open_positions;
+mod order;
+mod order_book;
+mod send_order;
+mod tickers;
@@ -0,0 +1,27 @@
+use crate::kraken::futures::decimal;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenPositionsResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub open_positions: Option<Vec<OpenPosition>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenPosition {
+    /// Either "long" or "short".
+    pub side: String,
+    pub symbol: String,
+    #[serde(with = "decimal")]
+    pub price: BigDecimal,
+    pub fill_time: String,
+    #[serde(with = "decimal")]
+    pub size: BigDecimal,
+    #[serde(default, with = "decimal::option")]
+    pub unrealized_funding: Option<BigDecimal>,
+}
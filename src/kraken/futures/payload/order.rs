@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    /// A limit order.
+    Lmt,
+    /// A post-only limit order.
+    Post,
+    /// An immediate-or-cancel order.
+    Ioc,
+    /// A market order.
+    Mkt,
+    /// A stop order.
+    Stp,
+    TakeProfit,
+    TrailingStop,
+}
@@ -0,0 +1,54 @@
+use crate::kraken::futures::decimal;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct OrderBookInput {
+    pub symbol: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBookResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub order_book: Option<OrderBook>,
+}
+
+/// OrderBook lists each side of the book as (price, size) pairs,
+/// bids in descending and asks in ascending order of price.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderBook {
+    #[serde(with = "decimal::levels")]
+    pub bids: Vec<(BigDecimal, BigDecimal)>,
+    #[serde(with = "decimal::levels")]
+    pub asks: Vec<(BigDecimal, BigDecimal)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderBookResponse;
+    use bigdecimal::BigDecimal;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn parses_levels_as_decimals() {
+        let input = json!({
+            "result": "success",
+            "serverTime": "2019-09-05T16:33:50.734Z",
+            "orderBook": {
+                "bids": [[9400.1, 0.3], [9400, 12000]],
+                "asks": [[9400.5, 2500]]
+            }
+        });
+        let resp: OrderBookResponse = serde_json::from_value(input).unwrap();
+        let book = resp.order_book.unwrap();
+        let decimal = |s| BigDecimal::from_str(s).unwrap();
+        assert_eq!(book.bids[0], (decimal("9400.1"), decimal("0.3")));
+        assert_eq!(book.bids[1], (decimal("9400"), decimal("12000")));
+        assert_eq!(book.asks, vec![(decimal("9400.5"), decimal("2500"))]);
+    }
+}
@@ -0,0 +1,142 @@
+use super::{OrderType, Side};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// SendOrderInput describes an order to place with FuturesClient::send_order.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SendOrderInput {
+    pub order_type: OrderType,
+    pub symbol: String,
+    pub side: Side,
+    pub size: BigDecimal,
+    pub limit_price: Option<BigDecimal>,
+    pub stop_price: Option<BigDecimal>,
+    pub cli_ord_id: Option<String>,
+    /// One of "mark", "index" or "last", for stop orders.
+    pub trigger_signal: Option<String>,
+    pub reduce_only: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SerializableSendOrderInput {
+    pub order_type: OrderType,
+    pub symbol: String,
+    pub side: Side,
+    pub size: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cli_ord_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_signal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+}
+
+impl From<&SendOrderInput> for SerializableSendOrderInput {
+    fn from(input: &SendOrderInput) -> Self {
+        Self {
+            order_type: input.order_type,
+            symbol: input.symbol.clone(),
+            side: input.side,
+            size: input.size.to_string(),
+            limit_price: input.limit_price.as_ref().map(BigDecimal::to_string),
+            stop_price: input.stop_price.as_ref().map(BigDecimal::to_string),
+            cli_ord_id: input.cli_ord_id.clone(),
+            trigger_signal: input.trigger_signal.clone(),
+            reduce_only: input.reduce_only,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SendOrderResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub send_status: Option<SendStatus>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SendStatus {
+    pub order_id: Option<String>,
+    pub status: String,
+    #[serde(rename = "cliOrdId")]
+    pub cli_ord_id: Option<String>,
+    #[serde(rename = "receivedTime")]
+    pub received_time: Option<String>,
+    #[serde(rename = "orderEvents")]
+    pub order_events: Option<Vec<Value>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OrderType, SendOrderInput, SendOrderResponse, SerializableSendOrderInput, Side};
+    use bigdecimal::BigDecimal;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn form_encodes_in_camel_case() {
+        let order = SendOrderInput {
+            order_type: OrderType::Lmt,
+            symbol: "PI_XBTUSD".to_owned(),
+            side: Side::Buy,
+            size: BigDecimal::from(1),
+            limit_price: Some(BigDecimal::from_str("9400.1").unwrap()),
+            stop_price: None,
+            cli_ord_id: Some("my-order-1".to_owned()),
+            trigger_signal: None,
+            reduce_only: None,
+        };
+        let req = reqwest::Client::new()
+            .post("https://futures.kraken.com/derivatives/api/v3/sendorder")
+            .form(&SerializableSendOrderInput::from(&order))
+            .build()
+            .unwrap();
+        let body = req.body().unwrap().as_bytes().unwrap();
+        let expected =
+            "orderType=lmt&symbol=PI_XBTUSD&side=buy&size=1&limitPrice=9400.1&cliOrdId=my-order-1";
+        assert_eq!(std::str::from_utf8(body).unwrap(), expected);
+    }
+
+    #[test]
+    fn parses_send_status() {
+        let input = json!({
+            "result": "success",
+            "sendStatus": {
+                "order_id": "179f9af8-e45e-469d-b3e9-2fd4675cb7d0",
+                "status": "placed",
+                "receivedTime": "2019-09-05T16:33:50.734Z",
+                "orderEvents": []
+            },
+            "serverTime": "2019-09-05T16:33:50.734Z"
+        });
+        let resp: SendOrderResponse = serde_json::from_value(input).unwrap();
+        let status = resp.send_status.unwrap();
+        assert_eq!(status.status, "placed");
+        assert_eq!(
+            status.order_id.unwrap(),
+            "179f9af8-e45e-469d-b3e9-2fd4675cb7d0"
+        );
+    }
+
+    #[test]
+    fn parses_error_body() {
+        let input = json!({
+            "result": "error",
+            "error": "apiLimitExceeded",
+            "serverTime": "2019-09-05T16:33:50.734Z"
+        });
+        let resp: SendOrderResponse = serde_json::from_value(input).unwrap();
+        assert_eq!(resp.error.unwrap(), "apiLimitExceeded");
+        assert!(resp.send_status.is_none());
+    }
+}
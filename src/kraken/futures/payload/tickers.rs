@@ -0,0 +1,53 @@
+use crate::kraken::futures::decimal;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TickersResponse {
+    pub result: String,
+    pub error: Option<String>,
+    pub server_time: Option<String>,
+    pub tickers: Option<Vec<FuturesTicker>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FuturesTicker {
+    pub symbol: String,
+    pub tag: Option<String>,
+    pub pair: Option<String>,
+    #[serde(default, with = "decimal::option")]
+    pub mark_price: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub bid: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub bid_size: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub ask: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub ask_size: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub last: Option<BigDecimal>,
+    pub last_time: Option<String>,
+    #[serde(default, with = "decimal::option")]
+    pub last_size: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub vol_24h: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub volume_quote: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub open_interest: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub open_24h: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub high_24h: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub low_24h: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub funding_rate: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub funding_rate_prediction: Option<BigDecimal>,
+    pub suspended: bool,
+    pub post_only: Option<bool>,
+}
@@ -0,0 +1,305 @@
+use super::{FuturesMessage, FuturesSubscription};
+use crate::kraken::env::KrakenCredentials;
+use crate::kraken::futures::endpoints::{DEMO_WEBSOCKET, WEBSOCKET};
+use crate::kraken::signature::FuturesChallengeInput;
+use crate::kraken::websocket::connection::{self, Command, Handled, Protocol};
+use crate::kraken::websocket::{ReconnectPolicy, SequenceTracker, WebSocketEvent};
+use async_trait::async_trait;
+use flume::{Receiver, Sender};
+use serde_json::{json, Value};
+use url::Url;
+
+/// FuturesWebSocketClient maintains a connection to the Kraken Futures
+/// websocket API in a background task, reconnecting and resubscribing
+/// whenever the connection drops. Private feeds are authenticated by
+/// signing the challenge the server issues on every new connection.
+/// The constructors spawn the background task, so they must be awaited
+/// within a Tokio runtime, though the client may be created outside one.
+pub struct FuturesWebSocketClient {
+    commands: Sender<Command<FuturesSubscription>>,
+    events: Receiver<WebSocketEvent<FuturesMessage>>,
+}
+
+impl FuturesWebSocketClient {
+    /// Connects without credentials, allowing only public feeds.
+    pub async fn public(policy: ReconnectPolicy) -> Self {
+        Self::connect(Url::parse(WEBSOCKET).unwrap(), None, policy).await
+    }
+
+    /// Connects with credentials, allowing both public and private feeds.
+    pub async fn private(creds: KrakenCredentials, policy: ReconnectPolicy) -> Self {
+        Self::connect(Url::parse(WEBSOCKET).unwrap(), Some(creds), policy).await
+    }
+
+    /// Connects to the demo environment at `demo-futures.kraken.com`.
+    pub async fn demo(creds: Option<KrakenCredentials>, policy: ReconnectPolicy) -> Self {
+        Self::connect(Url::parse(DEMO_WEBSOCKET).unwrap(), creds, policy).await
+    }
+
+    /// Connects to `url`, such as a local test server.
+    pub async fn connect(
+        url: Url,
+        creds: Option<KrakenCredentials>,
+        policy: ReconnectPolicy,
+    ) -> Self {
+        let protocol = FuturesProtocol {
+            credentials: creds,
+            challenge: None,
+            pending: Vec::new(),
+            sequences: SequenceTracker::new(),
+        };
+        let (commands, events) = connection::spawn(url, policy, protocol);
+        Self { commands, events }
+    }
+
+    /// Subscribes to a feed. The subscription is replayed
+    /// every time the client reconnects until it is unsubscribed.
+    pub fn subscribe(&self, subscription: FuturesSubscription) {
+        let _ = self.commands.send(Command::Subscribe(subscription));
+    }
+
+    pub fn unsubscribe(&self, subscription: FuturesSubscription) {
+        let _ = self.commands.send(Command::Unsubscribe(subscription));
+    }
+
+    /// Waits for the next event. Returns None once the
+    /// client has given up reconnecting.
+    pub async fn next_event(&self) -> Option<WebSocketEvent<FuturesMessage>> {
+        self.events.recv_async().await.ok()
+    }
+}
+
+struct SignedChallenge {
+    original: String,
+    signed: String,
+}
+
+struct FuturesProtocol {
+    credentials: Option<KrakenCredentials>,
+    // The challenge for the current connection, once the server has sent it.
+    challenge: Option<SignedChallenge>,
+    // Private subscriptions waiting for the challenge.
+    pending: Vec<FuturesSubscription>,
+    sequences: SequenceTracker,
+}
+
+impl FuturesProtocol {
+    fn message(&mut self, event: &str, sub: &FuturesSubscription) -> Vec<Value> {
+        let mut msg = json!({"event": event, "feed": sub.feed});
+        if !sub.product_ids.is_empty() {
+            msg["product_ids"] = json!(sub.product_ids);
+        }
+        if sub.feed.is_private() {
+            match (&self.credentials, &self.challenge) {
+                (Some(creds), Some(challenge)) => {
+                    msg["api_key"] = json!(creds.api_key());
+                    msg["original_challenge"] = json!(challenge.original);
+                    msg["signed_challenge"] = json!(challenge.signed);
+                }
+                // Hold the subscription until the challenge arrives.
+                (Some(_), None) => {
+                    self.pending.retain(|pending| pending != sub);
+                    if event == "subscribe" {
+                        self.pending.push(sub.clone());
+                    }
+                    return Vec::new();
+                }
+                // Without credentials, let Kraken reject the
+                // request so the error reaches the consumer.
+                (None, _) => (),
+            }
+        }
+        vec![msg]
+    }
+
+    fn on_challenge(&mut self, challenge: &str) -> Vec<Value> {
+        let creds = match &self.credentials {
+            Some(creds) => creds,
+            None => return Vec::new(),
+        };
+        let signed = FuturesChallengeInput {
+            private_key: creds.private_key().to_string(),
+            challenge: challenge.to_owned(),
+        }
+        .sign();
+        self.challenge = Some(SignedChallenge {
+            original: challenge.to_owned(),
+            signed,
+        });
+        let pending = std::mem::take(&mut self.pending);
+        pending
+            .iter()
+            .flat_map(|sub| self.message("subscribe", sub))
+            .collect()
+    }
+
+    // Book updates are numbered per product, starting from the snapshot.
+    fn check_sequence(&mut self, msg: &FuturesMessage) -> Option<WebSocketEvent<FuturesMessage>> {
+        match msg {
+            FuturesMessage::BookSnapshot(snapshot) => {
+                let channel = format!("book:{}", snapshot.product_id);
+                self.sequences.start(&channel, snapshot.seq);
+                None
+            }
+            FuturesMessage::Book(update) => {
+                let channel = format!("book:{}", update.product_id);
+                let gap = self.sequences.observe(&channel, update.seq)?;
+                Some(WebSocketEvent::GapDetected(gap))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl Protocol for FuturesProtocol {
+    type Subscription = FuturesSubscription;
+    type Message = FuturesMessage;
+
+    fn on_connect(&mut self) -> Vec<Value> {
+        self.challenge = None;
+        self.pending.clear();
+        self.sequences.reset();
+        // The heartbeat feed keeps quiet connections from
+        // tripping the ReconnectPolicy's idle timeout.
+        let mut msgs = vec![json!({"event": "subscribe", "feed": "heartbeat"})];
+        if let Some(creds) = &self.credentials {
+            msgs.push(json!({"event": "challenge", "api_key": creds.api_key()}));
+        }
+        msgs
+    }
+
+    fn subscribe(&mut self, sub: &FuturesSubscription) -> Vec<Value> {
+        self.message("subscribe", sub)
+    }
+
+    fn unsubscribe(&mut self, sub: &FuturesSubscription) -> Vec<Value> {
+        self.message("unsubscribe", sub)
+    }
+
+    fn on_message(&mut self, msg: Value) -> Handled<FuturesMessage> {
+        let mut handled = Handled::default();
+        if msg.get("feed").and_then(Value::as_str) == Some("heartbeat") {
+            return handled;
+        }
+        let event = msg.get("event").and_then(Value::as_str);
+        let challenge = msg.get("message").and_then(Value::as_str);
+        if let (Some("challenge"), Some(challenge)) = (event, challenge) {
+            handled.replies = self.on_challenge(challenge);
+            return handled;
+        }
+        // Acknowledgements, alerts and errors are passed on as they are.
+        let parsed = match event {
+            Some(_) => FuturesMessage::Other(msg),
+            None => serde_json::from_value(msg.clone()).unwrap_or(FuturesMessage::Other(msg)),
+        };
+        if let Some(gap) = self.check_sequence(&parsed) {
+            handled.events.push(gap);
+        }
+        handled.events.push(WebSocketEvent::Message(parsed));
+        handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FuturesWebSocketClient;
+    use crate::kraken::env::KrakenCredentials;
+    use crate::kraken::futures::websocket::{FuturesFeed, FuturesMessage, FuturesSubscription};
+    use crate::kraken::websocket::{ReconnectPolicy, WebSocketEvent};
+    use futures_util::{SinkExt, StreamExt};
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::WebSocketStream;
+    use url::Url;
+
+    const PRIVATE_KEY: &str =
+        "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==";
+    const CHALLENGE: &str = "c100b894-1729-464d-ace1-52dbce11db42";
+
+    async fn next_text(socket: &mut WebSocketStream<TcpStream>) -> Value {
+        loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    async fn send(socket: &mut WebSocketStream<TcpStream>, msg: Value) {
+        socket.send(Message::Text(msg.to_string())).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn signs_challenge_and_reports_book_gaps() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let heartbeat = json!({"event": "subscribe", "feed": "heartbeat"});
+            assert_eq!(next_text(&mut socket).await, heartbeat);
+            let challenge = json!({"event": "challenge", "api_key": "key"});
+            assert_eq!(next_text(&mut socket).await, challenge);
+            // The public subscription goes out without waiting.
+            let book = json!({"event": "subscribe", "feed": "book", "product_ids": ["PI_XBTUSD"]});
+            assert_eq!(next_text(&mut socket).await, book);
+            send(
+                &mut socket,
+                json!({"event": "challenge", "message": CHALLENGE}),
+            )
+            .await;
+            let fills = json!({
+                "event": "subscribe",
+                "feed": "fills",
+                "api_key": "key",
+                "original_challenge": CHALLENGE,
+                "signed_challenge": "I60DEDEo0uSpvAZDEDzQEN2E7nxXdIfsjIjlfxoZrgKETP11L657QPkKIKrvjmBP2g+YjKzij3wUaZuAwNaG1A==",
+            });
+            assert_eq!(next_text(&mut socket).await, fills);
+            let snapshot = json!({
+                "feed": "book_snapshot", "product_id": "PI_XBTUSD", "seq": 1,
+                "timestamp": 1612269825817u64, "bids": [{"price": 34892.5, "qty": 6385}], "asks": []
+            });
+            send(&mut socket, snapshot).await;
+            let update = json!({
+                "feed": "book", "product_id": "PI_XBTUSD", "side": "sell", "seq": 3,
+                "price": 34981.0, "qty": 0.0, "timestamp": 1612269953629u64
+            });
+            send(&mut socket, update).await;
+            while socket.next().await.is_some() {}
+        });
+
+        let url = Url::parse(&format!("ws://{}", addr)).unwrap();
+        let creds = KrakenCredentials::new("key".to_owned(), PRIVATE_KEY.to_owned());
+        let client =
+            FuturesWebSocketClient::connect(url, Some(creds), ReconnectPolicy::default()).await;
+        client.subscribe(FuturesSubscription::private(FuturesFeed::Fills));
+        client.subscribe(FuturesSubscription::public(
+            FuturesFeed::Book,
+            vec!["PI_XBTUSD".to_owned()],
+        ));
+        assert!(matches!(
+            client.next_event().await,
+            Some(WebSocketEvent::Connected)
+        ));
+        assert!(matches!(
+            client.next_event().await,
+            Some(WebSocketEvent::Message(FuturesMessage::BookSnapshot(_)))
+        ));
+        match client.next_event().await {
+            Some(WebSocketEvent::GapDetected(gap)) => {
+                assert_eq!(gap.channel, "book:PI_XBTUSD");
+                assert_eq!(gap.expected, 2);
+            }
+            other => panic!("expected a gap, got {:?}", other),
+        }
+        assert!(matches!(
+            client.next_event().await,
+            Some(WebSocketEvent::Message(FuturesMessage::Book(_)))
+        ));
+        drop(client);
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,293 @@
+use crate::kraken::futures::decimal;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// FuturesMessage is a message received on one of the Kraken Futures
+/// websocket feeds. Messages the client doesn't model, such as
+/// subscription acknowledgements and alerts, are passed on as `Other`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "feed", rename_all = "snake_case")]
+pub enum FuturesMessage {
+    // Boxed since a ticker is several times larger than the other messages.
+    Ticker(Box<TickerMessage>),
+    Trade(TradeMessage),
+    TradeSnapshot(TradeSnapshot),
+    Book(BookUpdate),
+    BookSnapshot(BookSnapshot),
+    Fills(FillsMessage),
+    FillsSnapshot(FillsMessage),
+    OpenOrders(OpenOrdersUpdate),
+    OpenOrdersSnapshot(OpenOrdersSnapshot),
+    OpenPositions(OpenPositionsMessage),
+    Balances(BalancesMessage),
+    BalancesSnapshot(BalancesMessage),
+    #[serde(skip)]
+    Other(Value),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TickerMessage {
+    pub product_id: String,
+    pub time: Option<u64>,
+    #[serde(default, with = "decimal::option")]
+    pub bid: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub ask: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub bid_size: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub ask_size: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub last: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub volume: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub change: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub index: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub premium: Option<BigDecimal>,
+    #[serde(default, rename = "markPrice", with = "decimal::option")]
+    pub mark_price: Option<BigDecimal>,
+    #[serde(default, rename = "openInterest", with = "decimal::option")]
+    pub open_interest: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub funding_rate: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub funding_rate_prediction: Option<BigDecimal>,
+    pub tag: Option<String>,
+    pub pair: Option<String>,
+    #[serde(default)]
+    pub suspended: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TradeMessage {
+    pub product_id: String,
+    pub uid: Option<String>,
+    pub side: String,
+    #[serde(rename = "type")]
+    pub trade_type: Option<String>,
+    pub seq: u64,
+    pub time: u64,
+    #[serde(with = "decimal")]
+    pub qty: BigDecimal,
+    #[serde(with = "decimal")]
+    pub price: BigDecimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TradeSnapshot {
+    pub product_id: String,
+    pub trades: Vec<TradeMessage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BookUpdate {
+    pub product_id: String,
+    /// Either "buy" or "sell".
+    pub side: String,
+    pub seq: u64,
+    #[serde(with = "decimal")]
+    pub price: BigDecimal,
+    /// The new quantity at this price. Zero removes the level.
+    #[serde(with = "decimal")]
+    pub qty: BigDecimal,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BookSnapshot {
+    pub product_id: String,
+    pub seq: u64,
+    pub timestamp: u64,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BookLevel {
+    #[serde(with = "decimal")]
+    pub price: BigDecimal,
+    #[serde(with = "decimal")]
+    pub qty: BigDecimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FillsMessage {
+    pub account: Option<String>,
+    pub username: Option<String>,
+    pub fills: Vec<FillMessage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FillMessage {
+    pub instrument: String,
+    pub time: u64,
+    #[serde(with = "decimal")]
+    pub price: BigDecimal,
+    pub seq: u64,
+    pub buy: bool,
+    #[serde(with = "decimal")]
+    pub qty: BigDecimal,
+    pub order_id: String,
+    pub cli_ord_id: Option<String>,
+    pub fill_id: String,
+    pub fill_type: String,
+    #[serde(default, with = "decimal::option")]
+    pub fee_paid: Option<BigDecimal>,
+    pub fee_currency: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenOrdersSnapshot {
+    pub account: Option<String>,
+    pub orders: Vec<OpenOrderMessage>,
+}
+
+/// OpenOrdersUpdate carries the full order when it is placed or
+/// changed, and only the `order_id` when it is cancelled or filled.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenOrdersUpdate {
+    pub order: Option<OpenOrderMessage>,
+    pub order_id: Option<String>,
+    pub is_cancel: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenOrderMessage {
+    pub instrument: String,
+    pub time: u64,
+    pub last_update_time: Option<u64>,
+    #[serde(with = "decimal")]
+    pub qty: BigDecimal,
+    #[serde(with = "decimal")]
+    pub filled: BigDecimal,
+    #[serde(default, with = "decimal::option")]
+    pub limit_price: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub stop_price: Option<BigDecimal>,
+    #[serde(rename = "type")]
+    pub order_type: String,
+    pub order_id: String,
+    pub cli_ord_id: Option<String>,
+    /// 0 for a buy order, 1 for a sell order.
+    pub direction: u8,
+    #[serde(default)]
+    pub reduce_only: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenPositionsMessage {
+    pub account: Option<String>,
+    pub positions: Vec<PositionMessage>,
+    pub seq: Option<u64>,
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PositionMessage {
+    pub instrument: String,
+    #[serde(with = "decimal")]
+    pub balance: BigDecimal,
+    #[serde(default, with = "decimal::option")]
+    pub pnl: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub entry_price: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub mark_price: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub index_price: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub liquidation_threshold: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub effective_leverage: Option<BigDecimal>,
+    #[serde(default, with = "decimal::option")]
+    pub return_on_equity: Option<BigDecimal>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BalancesMessage {
+    pub account: Option<String>,
+    pub seq: Option<u64>,
+    pub timestamp: Option<u64>,
+    /// Balances held in the cash account, keyed by currency.
+    #[serde(default, with = "decimal::map")]
+    pub holding: Option<HashMap<String, BigDecimal>>,
+    /// Margin account details, keyed by account name.
+    pub futures: Option<HashMap<String, Value>>,
+    /// Multi-collateral account details.
+    pub flex_futures: Option<Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FuturesMessage;
+    use bigdecimal::BigDecimal;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn parses_ticker() {
+        let input = json!({
+            "feed": "ticker", "product_id": "PI_XBTUSD", "bid": 34832.5, "ask": 34847.5,
+            "bid_size": 42864, "ask_size": 2300, "volume": 262306237, "dtm": 0,
+            "leverage": "50x", "index": 34803.45, "premium": 0.1, "last": 34852,
+            "time": 1612269825817u64, "change": 3.36, "funding_rate": 3.67e-10,
+            "funding_rate_prediction": 1.86e-10, "suspended": false, "tag": "perpetual",
+            "pair": "XBT:USD", "openInterest": 107706940, "markPrice": 34844.63
+        });
+        match serde_json::from_value(input).unwrap() {
+            FuturesMessage::Ticker(ticker) => {
+                assert_eq!(ticker.product_id, "PI_XBTUSD");
+                let mark_price = BigDecimal::from_str("34844.63").unwrap();
+                assert_eq!(ticker.mark_price, Some(mark_price));
+                let funding_rate = BigDecimal::from_str("0.000000000367").unwrap();
+                assert_eq!(ticker.funding_rate, Some(funding_rate));
+                assert_eq!(ticker.bid_size, Some(BigDecimal::from(42864)));
+            }
+            other => panic!("expected a ticker, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_book_levels_without_binary_noise() {
+        let input = json!({
+            "feed": "book_snapshot", "product_id": "PI_XBTUSD", "timestamp": 1612269825817u64,
+            "seq": 326072249, "tickSize": null,
+            "bids": [{"price": 34892.5, "qty": 6385}, {"price": 34892.0, "qty": 0.1}],
+            "asks": [{"price": 34911.5, "qty": 20598}]
+        });
+        match serde_json::from_value(input).unwrap() {
+            FuturesMessage::BookSnapshot(book) => {
+                let bid = &book.bids[1];
+                assert_eq!(bid.price, BigDecimal::from_str("34892.0").unwrap());
+                assert_eq!(bid.qty, BigDecimal::from_str("0.1").unwrap());
+                let level = serde_json::to_value(&book.asks[0]).unwrap();
+                assert_eq!(level, json!({"price": "34911.5", "qty": "20598"}));
+            }
+            other => panic!("expected a book snapshot, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_cancelled_order() {
+        let input = json!({
+            "feed": "open_orders",
+            "order_id": "660c6b23-8007-48c1-a7c9-4893f4572e8c",
+            "is_cancel": true,
+            "reason": "cancelled_by_user"
+        });
+        match serde_json::from_value(input).unwrap() {
+            FuturesMessage::OpenOrders(update) => {
+                assert!(update.is_cancel);
+                assert!(update.order.is_none());
+            }
+            other => panic!("expected an order update, got {:?}", other),
+        }
+    }
+}
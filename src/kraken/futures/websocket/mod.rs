@@ -0,0 +1,11 @@
+pub use client::FuturesWebSocketClient;
+pub use message::{
+    BalancesMessage, BookLevel, BookSnapshot, BookUpdate, FillMessage, FillsMessage,
+    FuturesMessage, OpenOrderMessage, OpenOrdersSnapshot, OpenOrdersUpdate, OpenPositionsMessage,
+    PositionMessage, TickerMessage, TradeMessage, TradeSnapshot,
+};
+pub use subscription::{FuturesFeed, FuturesSubscription};
+
+mod client;
+mod message;
+mod subscription;
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FuturesFeed {
+    Ticker,
+    Trade,
+    Book,
+    Fills,
+    OpenOrders,
+    OpenPositions,
+    Balances,
+}
+
+impl FuturesFeed {
+    /// Private feeds require a signed challenge to subscribe.
+    pub fn is_private(&self) -> bool {
+        matches!(
+            self,
+            Self::Fills | Self::OpenOrders | Self::OpenPositions | Self::Balances
+        )
+    }
+}
+
+/// FuturesSubscription is a single feed subscription on the Kraken
+/// Futures websocket API. Public feeds are subscribed per product,
+/// private feeds cover the whole account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuturesSubscription {
+    pub feed: FuturesFeed,
+    pub product_ids: Vec<String>,
+}
+
+impl FuturesSubscription {
+    pub fn public(feed: FuturesFeed, product_ids: Vec<String>) -> Self {
+        Self { feed, product_ids }
+    }
+
+    pub fn private(feed: FuturesFeed) -> Self {
+        Self {
+            feed,
+            product_ids: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::kraken::payload::kraken_errors;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Method, Request, StatusCode};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Headers carrying credentials or signatures, for the spot and futures APIs.
+const SECRET_HEADERS: [&str; 4] = ["API-Key", "API-Sign", "APIKey", "Authent"];
+const REDACTED: &str = "[redacted]";
+
+/// Middleware observes every request the Client sends, for logging,
+/// metrics or auditing. Both hooks are called once per attempt, so a
+/// retried request is seen several times. They run on the task sending
+/// the request and should return quickly. Every method has a default
+/// which does nothing, so only the hooks of interest need implementing.
+pub trait Middleware: Send + Sync {
+    /// Called just before the request is sent.
+    fn before_request(&self, _req: &RequestInfo) {}
+
+    /// Called once the response body has been read,
+    /// or the request has failed without a response.
+    fn after_response(&self, _req: &RequestInfo, _resp: &ResponseInfo) {}
+}
+
+// Lets the caller keep a handle on the middleware, e.g. to read its counters.
+impl<M: Middleware + ?Sized> Middleware for Arc<M> {
+    fn before_request(&self, req: &RequestInfo) {
+        (**self).before_request(req)
+    }
+
+    fn after_response(&self, req: &RequestInfo, resp: &ResponseInfo) {
+        (**self).after_response(req, resp)
+    }
+}
+
+/// RequestInfo describes a request about to be sent.
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    pub method: Method,
+    /// The URL path, such as "/0/private/Balance".
+    pub path: String,
+    /// The request headers, with the API key and signature redacted.
+    pub headers: HeaderMap,
+    /// Whether the request is signed with the account's credentials.
+    pub private: bool,
+}
+
+impl RequestInfo {
+    pub(crate) fn new(req: &Request, private: bool) -> Self {
+        Self {
+            method: req.method().clone(),
+            path: req.url().path().to_owned(),
+            headers: redact(req.headers()),
+            private,
+        }
+    }
+}
+
+/// ResponseInfo describes the outcome of a request.
+#[derive(Debug, Clone)]
+pub struct ResponseInfo {
+    /// The HTTP status, or None if no response arrived,
+    /// such as after a connection error or a timeout.
+    pub status: Option<StatusCode>,
+    /// The time from sending the request until the body was read.
+    pub latency: Duration,
+    /// The error codes Kraken returned, such as "EOrder:Insufficient funds".
+    pub errors: Vec<String>,
+}
+
+impl ResponseInfo {
+    pub(crate) fn new(
+        result: &Result<(StatusCode, Vec<u8>), reqwest::Error>,
+        latency: Duration,
+    ) -> Self {
+        match result {
+            Ok((status, body)) => Self {
+                status: Some(*status),
+                latency,
+                errors: kraken_errors(body),
+            },
+            Err(err) => Self {
+                status: err.status(),
+                latency,
+                errors: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Returns a copy of the headers with credentials replaced by a placeholder.
+pub(crate) fn redact(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for name in SECRET_HEADERS {
+        if let Some(value) = headers.get_mut(name) {
+            *value = HeaderValue::from_static(REDACTED);
+        }
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::redact;
+    use pretty_assertions::assert_eq;
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+
+    #[test]
+    fn redacts_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert("API-Key", HeaderValue::from_static("key"));
+        headers.insert("API-Sign", HeaderValue::from_static("signature"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        let headers = redact(&headers);
+        assert_eq!(headers["API-Key"], "[redacted]");
+        assert_eq!(headers["API-Sign"], "[redacted]");
+        assert_eq!(headers[CONTENT_TYPE], "text/plain");
+    }
+}
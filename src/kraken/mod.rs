@@ -1,19 +1,55 @@
-pub use account_tier::AccountTier;
-pub use asset_pair::AssetPair;
-pub use client::Client;
+pub use account_tier::{AccountTier, CounterLimit, InvalidLimitError};
+pub use asset::{Asset, AssetNormalizer};
+pub use asset_pair::{AssetPair, ParsePairError};
+pub use authentication::{Authenticated, Public};
+pub use client::{Client, PublicClient};
+pub use client_builder::ClientBuilder;
+pub use client_error::ClientError;
+#[allow(deprecated)]
+pub use endpoints::endpoint;
 pub use endpoints::{
-    endpoint, ACCOUNT_BALANCE, ASSET_INFO, ASSET_PAIRS, OPEN_ORDERS, RECENT_SPREADS, SYSTEM_STATUS,
-    SYSTEM_TIME, TICKER, TRADE_BALANCE,
+    ACCOUNT_BALANCE, ADD_ORDER, ASSET_INFO, ASSET_PAIRS, CANCEL_ORDER, CLOSED_ORDERS,
+    DEFAULT_API_VERSION, DEFAULT_BASE_URL, OPEN_ORDERS, RECENT_SPREADS, SYSTEM_STATUS, SYSTEM_TIME,
+    TICKER, TRADE_BALANCE, WEBSOCKET_PRIVATE, WEBSOCKET_PUBLIC,
 };
 pub use env::KrakenCredentials;
-pub use payload::TickerInfo;
+pub use middleware::{Middleware, RequestInfo, ResponseInfo};
+pub use nonce::{AtomicNonce, FileNonce, NonceProvider};
+pub use order_validator::{OrderError, OrderValidator};
+pub use pair_registry::PairRegistry;
+pub use payload::{
+    AddOrderInput, AddOrderResponse, AddOrderResult, AssetInfo, AssetPairInfo, AssetPairsInfo,
+    AssetPairsResponse, AssetPairsShape, AssetStatus, CancelOrderResponse, CancelOrderResult,
+    OrderDescription, OrderSide, OrderType, PairFees, PairLeverage, PairMargin, PairStatus,
+    RecentSpreadsResponse, Spread, TickerInfo, TickerResponse,
+};
+pub use ratelimiter::{endpoint_cost, FileRateLimiter, LeakyBucket, RateLimiter};
+pub use retry_policy::RetryPolicy;
 
 mod account_tier;
+mod asset;
 mod asset_pair;
+mod authentication;
+mod backoff;
 mod client;
+mod client_builder;
+mod client_error;
 mod endpoints;
 mod env;
+pub mod futures;
+mod middleware;
+mod nonce;
+mod order_validator;
+mod pair_registry;
 mod payload;
 mod ratelimiter;
 mod request_builder;
+mod retry_policy;
 mod signature;
+#[cfg(test)]
+mod test_server;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trace;
+mod transport;
+pub mod websocket;
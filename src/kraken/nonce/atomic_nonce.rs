@@ -0,0 +1,81 @@
+use super::NonceProvider;
+use async_trait::async_trait;
+use chrono::prelude::*;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// AtomicNonce is the default NonceProvider. Nonces are the current time
+/// in microseconds, bumped past the last nonce handed out whenever two
+/// calls land in the same microsecond or the system clock steps back.
+/// It is only collision-free within one process; use a FileNonce when
+/// several processes share an API key.
+#[derive(Debug, Default)]
+pub struct AtomicNonce {
+    last: AtomicU64,
+}
+
+impl AtomicNonce {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the next nonce. Unlike next_nonce, this cannot fail.
+    pub fn next(&self) -> u64 {
+        let now = now_micros();
+        let prev = self
+            .last
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap();
+        now.max(prev + 1)
+    }
+}
+
+#[async_trait]
+impl NonceProvider for AtomicNonce {
+    async fn next_nonce(&self) -> io::Result<u64> {
+        Ok(self.next())
+    }
+}
+
+pub(crate) fn now_micros() -> u64 {
+    let utc: DateTime<Utc> = Utc::now();
+    utc.timestamp_micros() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AtomicNonce;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn nonces_strictly_increase() {
+        let nonces = AtomicNonce::new();
+        let mut last = nonces.next();
+        for _ in 0..10_000 {
+            let next = nonces.next();
+            assert!(next > last);
+            last = next;
+        }
+    }
+
+    #[test]
+    fn nonces_are_unique_across_threads() {
+        let nonces = Arc::new(AtomicNonce::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let nonces = nonces.clone();
+                thread::spawn(move || (0..1000).map(|_| nonces.next()).collect::<Vec<_>>())
+            })
+            .collect();
+        let mut seen = HashSet::new();
+        for handle in handles {
+            for nonce in handle.join().unwrap() {
+                assert!(seen.insert(nonce));
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use super::atomic_nonce::now_micros;
+use super::NonceProvider;
+use async_trait::async_trait;
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// FileNonce stores the last nonce in a file, so processes on one host
+/// that share an API key never reuse each other's nonces. Each call takes
+/// an exclusive lock on the file, reads the last nonce, and writes the new
+/// one before releasing the lock. Waiting for the lock and syncing the
+/// file block, so this is done on Tokio's blocking thread pool.
+#[derive(Debug, Clone)]
+pub struct FileNonce {
+    path: PathBuf,
+}
+
+impl FileNonce {
+    /// The file is created on first use if it does not exist.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    fn next_blocking(&self) -> io::Result<u64> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        file.lock_exclusive()?;
+        let next = self.advance(&mut file);
+        file.unlock()?;
+        next
+    }
+
+    fn advance(&self, file: &mut File) -> io::Result<u64> {
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let last = match contents.trim() {
+            "" => 0,
+            text => text
+                .parse::<u64>()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+        };
+        let next = now_micros().max(last + 1);
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(next.to_string().as_bytes())?;
+        file.sync_data()?;
+        Ok(next)
+    }
+}
+
+#[async_trait]
+impl NonceProvider for FileNonce {
+    async fn next_nonce(&self) -> io::Result<u64> {
+        let nonces = self.clone();
+        tokio::task::spawn_blocking(move || nonces.next_blocking())
+            .await
+            .unwrap_or_else(|err| Err(io::Error::other(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileNonce;
+    use crate::kraken::nonce::NonceProvider;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+
+    #[tokio::test]
+    async fn continues_from_the_stored_nonce() {
+        let path = std::env::temp_dir().join(format!("kraken-nonce-{}", std::process::id()));
+        // A nonce far in the future, as if another process had just used it.
+        fs::write(&path, "99999999999999999").unwrap();
+        let first = FileNonce::new(&path);
+        let second = FileNonce::new(&path);
+        assert_eq!(first.next_nonce().await.unwrap(), 100000000000000000);
+        assert_eq!(second.next_nonce().await.unwrap(), 100000000000000001);
+        let stored = fs::read_to_string(&path).unwrap();
+        assert_eq!(stored, "100000000000000001");
+        fs::remove_file(&path).unwrap();
+    }
+}
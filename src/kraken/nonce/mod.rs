@@ -0,0 +1,7 @@
+pub use atomic_nonce::AtomicNonce;
+pub use file_nonce::FileNonce;
+pub use nonce_provider::NonceProvider;
+
+mod atomic_nonce;
+mod file_nonce;
+mod nonce_provider;
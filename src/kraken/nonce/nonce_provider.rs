@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+use std::io;
+use std::sync::Arc;
+
+/// NonceProvider generates the nonces sent with private requests.
+/// Kraken rejects a request with `EAPI:Invalid nonce` unless its nonce
+/// is greater than every nonce previously used with the same API key,
+/// so every nonce returned must be strictly greater than the last one.
+/// Providers which block, such as on a file lock, should do so off the
+/// runtime's threads, as FileNonce does.
+#[async_trait]
+pub trait NonceProvider: Send + Sync {
+    async fn next_nonce(&self) -> io::Result<u64>;
+}
+
+// Lets one provider be shared between several clients.
+#[async_trait]
+impl<P: NonceProvider + ?Sized> NonceProvider for Arc<P> {
+    async fn next_nonce(&self) -> io::Result<u64> {
+        (**self).next_nonce().await
+    }
+}
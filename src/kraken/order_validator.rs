@@ -0,0 +1,357 @@
+use crate::kraken::payload::{AssetPairInfo, PairStatus};
+use crate::kraken::{AddOrderInput, OrderSide, OrderType};
+use bigdecimal::{BigDecimal, One, Signed, Zero};
+use std::fmt;
+use std::str::FromStr;
+
+/// OrderError describes why Kraken would reject an order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderError {
+    /// The pair isn't in the PairRegistry.
+    UnknownPair(String),
+    /// The pair's status doesn't allow this order.
+    PairRestricted(PairStatus),
+    /// The order type needs a price which wasn't given.
+    MissingPrice,
+    /// A price isn't a multiple of the pair's tick size.
+    InvalidPrice {
+        price: BigDecimal,
+        tick_size: BigDecimal,
+    },
+    /// The volume has more decimals than the pair allows.
+    InvalidVolume {
+        volume: BigDecimal,
+        lot_decimals: u64,
+    },
+    /// The volume is below the pair's minimum order size.
+    VolumeTooLow {
+        volume: BigDecimal,
+        minimum: BigDecimal,
+    },
+    /// The order's cost, in the quote asset, is below the pair's minimum.
+    CostTooLow {
+        cost: BigDecimal,
+        minimum: BigDecimal,
+    },
+    /// The leverage isn't offered for this pair and side.
+    InvalidLeverage(String),
+}
+
+impl std::error::Error for OrderError {}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPair(pair) => write!(f, "Unknown asset pair {}", pair),
+            Self::PairRestricted(status) => write!(f, "The pair is {}", status),
+            Self::MissingPrice => write!(f, "The order type requires a price"),
+            Self::InvalidPrice { price, tick_size } => write!(
+                f,
+                "Price {} is not a multiple of the tick size {}",
+                price, tick_size
+            ),
+            Self::InvalidVolume {
+                volume,
+                lot_decimals,
+            } => write!(
+                f,
+                "Volume {} has more than {} decimals",
+                volume, lot_decimals
+            ),
+            Self::VolumeTooLow { volume, minimum } => {
+                write!(f, "Volume {} is below the minimum of {}", volume, minimum)
+            }
+            Self::CostTooLow { cost, minimum } => {
+                write!(f, "Cost {} is below the minimum of {}", cost, minimum)
+            }
+            Self::InvalidLeverage(leverage) => {
+                write!(f, "Leverage {} is not available", leverage)
+            }
+        }
+    }
+}
+
+/// OrderValidator checks orders for one pair against the limits Kraken
+/// publishes through the AssetPairs endpoint, so an order which would be
+/// rejected with "EOrder:Invalid price" or "EGeneral:Invalid arguments"
+/// can be caught, or rounded, before it is sent.
+#[derive(Debug, Clone)]
+pub struct OrderValidator {
+    tick_size: BigDecimal,
+    lot_decimals: u64,
+    order_minimum: Option<BigDecimal>,
+    cost_minimum: Option<BigDecimal>,
+    status: Option<PairStatus>,
+    leverage_buy: Vec<u64>,
+    leverage_sell: Vec<u64>,
+}
+
+impl OrderValidator {
+    /// Builds the validator from the pair's info. Limits which
+    /// are missing or can't be parsed are not checked.
+    pub fn new(info: &AssetPairInfo) -> Self {
+        let parse = |value: &Option<String>| {
+            value
+                .as_deref()
+                .and_then(|value| BigDecimal::from_str(value).ok())
+        };
+        // Pairs listed before Kraken published tick sizes
+        // are priced to pair_decimals decimals.
+        let tick_size = parse(&info.tick_size)
+            .filter(|tick| tick > &BigDecimal::zero())
+            .unwrap_or_else(|| BigDecimal::new(1.into(), info.pair_decimals as i64));
+        Self {
+            tick_size,
+            lot_decimals: info.lot_decimals,
+            order_minimum: BigDecimal::from_str(&info.order_minimum).ok(),
+            cost_minimum: parse(&info.cost_minimum),
+            status: info.status,
+            leverage_buy: info.leverage_buy.clone(),
+            leverage_sell: info.leverage_sell.clone(),
+        }
+    }
+
+    /// Checks the order, returning the first limit it breaks.
+    /// A "reduce_only" pair can't be checked, since that depends
+    /// on the account's open positions.
+    pub fn validate(&self, order: &AddOrderInput) -> Result<(), OrderError> {
+        self.check_status(order)?;
+        let (trigger, limit) = self.prices(order)?;
+        for price in [trigger, limit].into_iter().flatten() {
+            if !(price % &self.tick_size).is_zero() {
+                return Err(OrderError::InvalidPrice {
+                    price: price.clone(),
+                    tick_size: self.tick_size.clone(),
+                });
+            }
+        }
+        if order.volume != self.round_volume(&order.volume) {
+            return Err(OrderError::InvalidVolume {
+                volume: order.volume.clone(),
+                lot_decimals: self.lot_decimals,
+            });
+        }
+        if let Some(minimum) = &self.order_minimum {
+            if &order.volume < minimum {
+                return Err(OrderError::VolumeTooLow {
+                    volume: order.volume.clone(),
+                    minimum: minimum.clone(),
+                });
+            }
+        }
+        // The cost of a market order isn't known until it fills.
+        if let (Some(minimum), Some(price)) = (&self.cost_minimum, limit.or(trigger)) {
+            let cost = &order.volume * price;
+            if &cost < minimum {
+                return Err(OrderError::CostTooLow {
+                    cost,
+                    minimum: minimum.clone(),
+                });
+            }
+        }
+        self.check_leverage(order)
+    }
+
+    /// Rounds the order's prices to the nearest tick and
+    /// its volume down to the pair's lot decimals.
+    pub fn round(&self, order: &mut AddOrderInput) {
+        order.price = order.price.as_ref().map(|price| self.round_price(price));
+        order.price2 = order.price2.as_ref().map(|price| self.round_price(price));
+        order.volume = self.round_volume(&order.volume);
+    }
+
+    /// Rounds the price to the nearest multiple of the tick size.
+    pub fn round_price(&self, price: &BigDecimal) -> BigDecimal {
+        let ticks = price / &self.tick_size;
+        let whole = ticks.with_scale(0);
+        let half = BigDecimal::new(5.into(), 1);
+        let ticks = match (&ticks - &whole).abs() >= half {
+            true if ticks.is_negative() => whole - BigDecimal::one(),
+            true => whole + BigDecimal::one(),
+            false => whole,
+        };
+        let (_, scale) = self.tick_size.as_bigint_and_exponent();
+        (ticks * &self.tick_size).with_scale(scale.max(0))
+    }
+
+    /// Rounds the volume down to the pair's lot decimals, so
+    /// the order never asks for more than the volume given.
+    pub fn round_volume(&self, volume: &BigDecimal) -> BigDecimal {
+        volume.with_scale(self.lot_decimals as i64)
+    }
+
+    fn check_status(&self, order: &AddOrderInput) -> Result<(), OrderError> {
+        let is_limit = order.order_type == OrderType::Limit;
+        let is_post = order
+            .oflags
+            .as_deref()
+            .is_some_and(|flags| flags.split(',').any(|flag| flag == "post"));
+        let allowed = match self.status {
+            Some(PairStatus::CancelOnly) => false,
+            Some(PairStatus::LimitOnly) => is_limit,
+            Some(PairStatus::PostOnly) => is_limit && is_post,
+            _ => true,
+        };
+        match (allowed, self.status) {
+            (false, Some(status)) => Err(OrderError::PairRestricted(status)),
+            _ => Ok(()),
+        }
+    }
+
+    // Returns the trigger price and the limit price the order type uses.
+    fn prices<'a>(
+        &self,
+        order: &'a AddOrderInput,
+    ) -> Result<(Option<&'a BigDecimal>, Option<&'a BigDecimal>), OrderError> {
+        let price = order.price.as_ref();
+        let price2 = order.price2.as_ref();
+        let prices = match order.order_type {
+            OrderType::Market | OrderType::SettlePosition => (None, None),
+            OrderType::Limit => (None, Some(price.ok_or(OrderError::MissingPrice)?)),
+            OrderType::StopLoss | OrderType::TakeProfit => {
+                (Some(price.ok_or(OrderError::MissingPrice)?), None)
+            }
+            OrderType::StopLossLimit | OrderType::TakeProfitLimit => (
+                Some(price.ok_or(OrderError::MissingPrice)?),
+                Some(price2.ok_or(OrderError::MissingPrice)?),
+            ),
+        };
+        Ok(prices)
+    }
+
+    fn check_leverage(&self, order: &AddOrderInput) -> Result<(), OrderError> {
+        let leverage = match order.leverage.as_deref() {
+            None | Some("none") => return Ok(()),
+            Some(leverage) => leverage,
+        };
+        // Leverage is given as "2" or "2:1".
+        let ratio = leverage.split(':').next().unwrap_or_default();
+        let offered = match order.side {
+            OrderSide::Buy => &self.leverage_buy,
+            OrderSide::Sell => &self.leverage_sell,
+        };
+        match ratio.trim().parse::<u64>() {
+            Ok(ratio) if offered.contains(&ratio) => Ok(()),
+            _ => Err(OrderError::InvalidLeverage(leverage.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OrderError, OrderValidator};
+    use crate::kraken::payload::{AssetPairInfo, PairStatus};
+    use crate::kraken::{AddOrderInput, OrderSide, OrderType};
+    use bigdecimal::BigDecimal;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn validator(status: &str) -> OrderValidator {
+        let info: AssetPairInfo = serde_json::from_value(json!({
+            "altname": "XBTUSD",
+            "wsname": "XBT/USD",
+            "aclass_base": "currency",
+            "base": "XXBT",
+            "aclass_quote": "currency",
+            "quote": "ZUSD",
+            "pair_decimals": 1,
+            "lot_decimals": 8,
+            "lot_multiplier": 1,
+            "leverage_buy": [2, 3],
+            "leverage_sell": [2],
+            "fees": [[0, 0.26]],
+            "fees_maker": [[0, 0.16]],
+            "fee_volume_currency": "ZUSD",
+            "margin_call": 80,
+            "margin_stop": 40,
+            "ordermin": "0.0001",
+            "costmin": "0.5",
+            "tick_size": "0.1",
+            "status": status
+        }))
+        .unwrap();
+        OrderValidator::new(&info)
+    }
+
+    fn limit(volume: &str, price: &str) -> AddOrderInput {
+        AddOrderInput {
+            pair: "XBTUSD".to_owned(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            volume: BigDecimal::from_str(volume).unwrap(),
+            price: Some(BigDecimal::from_str(price).unwrap()),
+            price2: None,
+            leverage: None,
+            userref: None,
+            oflags: None,
+            validate: None,
+        }
+    }
+
+    fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn checks_precision_and_minimums() {
+        let validator = validator("online");
+        assert_eq!(validator.validate(&limit("0.01", "30000.1")), Ok(()));
+        assert_eq!(
+            validator.validate(&limit("0.01", "30000.15")),
+            Err(OrderError::InvalidPrice {
+                price: decimal("30000.15"),
+                tick_size: decimal("0.1"),
+            })
+        );
+        assert!(matches!(
+            validator.validate(&limit("0.000000001", "30000")),
+            Err(OrderError::InvalidVolume { .. })
+        ));
+        assert!(matches!(
+            validator.validate(&limit("0.00005", "30000")),
+            Err(OrderError::VolumeTooLow { .. })
+        ));
+        assert_eq!(
+            validator.validate(&limit("0.0001", "1000")),
+            Err(OrderError::CostTooLow {
+                cost: decimal("0.1"),
+                minimum: decimal("0.5"),
+            })
+        );
+        let mut order = limit("0.01", "30000");
+        order.price = None;
+        assert_eq!(validator.validate(&order), Err(OrderError::MissingPrice));
+    }
+
+    #[test]
+    fn checks_status_and_leverage() {
+        let mut order = limit("0.01", "30000");
+        order.leverage = Some("3:1".to_owned());
+        assert_eq!(validator("online").validate(&order), Ok(()));
+        order.side = OrderSide::Sell;
+        assert_eq!(
+            validator("online").validate(&order),
+            Err(OrderError::InvalidLeverage("3:1".to_owned()))
+        );
+        let order = limit("0.01", "30000");
+        let restricted = OrderError::PairRestricted(PairStatus::PostOnly);
+        assert_eq!(validator("post_only").validate(&order), Err(restricted));
+        assert_eq!(validator("limit_only").validate(&order), Ok(()));
+        assert!(validator("cancel_only").validate(&order).is_err());
+    }
+
+    #[test]
+    fn rounds_to_valid_precision() {
+        let validator = validator("online");
+        let mut order = limit("0.123456789", "30000.15");
+        validator.round(&mut order);
+        assert_eq!(order.price, Some(decimal("30000.2")));
+        assert_eq!(order.volume, decimal("0.12345678"));
+        assert_eq!(
+            validator.round_price(&decimal("30000.14")),
+            decimal("30000.1")
+        );
+        assert_eq!(validator.validate(&order), Ok(()));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::kraken::payload::AssetPairInfo;
+use crate::kraken::{AddOrderInput, AssetPair, Client, ClientError, OrderError, OrderValidator};
+use std::collections::HashMap;
+
+/// PairRegistry holds the pairs Kraken lists, and resolves any name
+/// a pair goes by to the AssetPair Kraken keys it by in responses,
+/// such as "XXBTZUSD" for "XBTUSD" and "XBT/USD".
+pub struct PairRegistry {
+    pairs: HashMap<AssetPair, AssetPairInfo>,
+    // Every name of every pair, in upper case.
+    names: HashMap<String, AssetPair>,
+}
+
+impl PairRegistry {
+    /// Builds the registry from the result of the AssetPairs endpoint.
+    /// Pairs whose names aren't valid AssetPairs are left out.
+    pub fn new(pairs: HashMap<String, AssetPairInfo>) -> Self {
+        let mut registry = Self {
+            pairs: HashMap::new(),
+            names: HashMap::new(),
+        };
+        for (name, info) in pairs {
+            let pair = match name.parse::<AssetPair>() {
+                Ok(pair) => pair,
+                Err(_) => continue,
+            };
+            let aliases = [&name, &info.alt_name, &info.websocket_name];
+            for alias in aliases.into_iter().filter(|alias| !alias.is_empty()) {
+                registry
+                    .names
+                    .insert(alias.to_ascii_uppercase(), pair.clone());
+            }
+            registry.pairs.insert(pair, info);
+        }
+        registry
+    }
+
+    /// Loads every pair from the AssetPairs endpoint.
+    pub async fn load<A>(client: &Client<A>) -> Result<Self, ClientError> {
+        let resp = client.asset_pairs(Vec::new(), None).await?;
+        match resp.result {
+            Some(pairs) => Ok(Self::new(pairs)),
+            None => Err(ClientError::Kraken(resp.error)),
+        }
+    }
+
+    /// Returns the pair known by `name`, which may be
+    /// its legacy name, altname or websocket name.
+    pub fn resolve(&self, name: &str) -> Option<&AssetPair> {
+        self.names.get(&name.trim().to_ascii_uppercase())
+    }
+
+    /// Returns what Kraken reports about the pair known by `name`.
+    pub fn info(&self, name: &str) -> Option<&AssetPairInfo> {
+        self.resolve(name).and_then(|pair| self.pairs.get(pair))
+    }
+
+    /// Returns an OrderValidator for the pair known by `name`.
+    pub fn validator(&self, name: &str) -> Option<OrderValidator> {
+        self.info(name).map(OrderValidator::new)
+    }
+
+    /// Checks the order against the limits of its pair,
+    /// before sending it to Client::add_order.
+    pub fn validate(&self, order: &AddOrderInput) -> Result<(), OrderError> {
+        match self.validator(&order.pair) {
+            Some(validator) => validator.validate(order),
+            None => Err(OrderError::UnknownPair(order.pair.clone())),
+        }
+    }
+
+    /// Returns every pair, by the name Kraken keys it by.
+    pub fn pairs(&self) -> impl Iterator<Item = &AssetPair> {
+        self.pairs.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PairRegistry;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn resolves_every_name_of_a_pair() {
+        let pairs = json!({
+            "XXBTZUSD": {
+                "altname": "XBTUSD",
+                "wsname": "XBT/USD",
+                "aclass_base": "currency",
+                "base": "XXBT",
+                "aclass_quote": "currency",
+                "quote": "ZUSD",
+                "pair_decimals": 1,
+                "lot_decimals": 8,
+                "lot_multiplier": 1,
+                "leverage_buy": [2, 3, 4, 5],
+                "leverage_sell": [2, 3, 4, 5],
+                "fees": [[0, 0.26]],
+                "fees_maker": [[0, 0.16]],
+                "fee_volume_currency": "ZUSD",
+                "margin_call": 80,
+                "margin_stop": 40,
+                "ordermin": "0.0001"
+            }
+        });
+        let registry = PairRegistry::new(serde_json::from_value(pairs).unwrap());
+        assert_eq!(registry.len(), 1);
+        for name in ["XXBTZUSD", "XBTUSD", "XBT/USD", "xbt/usd"] {
+            let pair = registry.resolve(name).unwrap();
+            assert_eq!(pair.as_str(), "XXBTZUSD");
+        }
+        assert_eq!(registry.info("XBTUSD").unwrap().base, "XXBT");
+        assert!(registry.resolve("ETHUSD").is_none());
+    }
+}
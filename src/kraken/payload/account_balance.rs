@@ -1,3 +1,4 @@
+use crate::kraken::Asset;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
@@ -7,13 +8,16 @@ pub struct AccountBalanceInput {
     pub nonce: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct AccountBalanceResponse {
-    error: Vec<String>,
-    result: AccountBalanceResult,
+    pub error: Vec<String>,
+    #[serde(default)]
+    pub result: AccountBalanceResult,
 }
 
-type AccountBalanceResult = HashMap<String, String>;
+/// The balance of each asset, keyed by its normalized name.
+pub type AccountBalanceResult = HashMap<Asset, String>;
 
 impl fmt::Display for AccountBalanceResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -0,0 +1,201 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrderType {
+    Market,
+    Limit,
+    StopLoss,
+    TakeProfit,
+    StopLossLimit,
+    TakeProfitLimit,
+    SettlePosition,
+}
+
+/// AddOrderInput describes an order to place with Client::add_order.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AddOrderInput {
+    pub pair: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub volume: BigDecimal,
+    /// The limit price, or the trigger price for stop and take-profit orders.
+    pub price: Option<BigDecimal>,
+    /// The limit price for stop-loss-limit and take-profit-limit orders.
+    pub price2: Option<BigDecimal>,
+    /// The leverage to use, such as "2:1". None places the order without margin.
+    pub leverage: Option<String>,
+    /// A reference the order can later be looked up or cancelled by.
+    pub userref: Option<i32>,
+    /// Comma separated order flags, such as "post,fciq".
+    pub oflags: Option<String>,
+    /// Validates the order without placing it.
+    pub validate: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializableAddOrderInput {
+    pub nonce: String,
+    pub pair: String,
+    #[serde(rename = "type")]
+    pub side: OrderSide,
+    #[serde(rename = "ordertype")]
+    pub order_type: OrderType,
+    pub volume: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price2: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leverage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userref: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oflags: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate: Option<bool>,
+}
+
+impl SerializableAddOrderInput {
+    pub fn new(nonce: String, input: &AddOrderInput) -> Self {
+        Self {
+            nonce,
+            pair: input.pair.clone(),
+            side: input.side,
+            order_type: input.order_type,
+            volume: input.volume.to_string(),
+            price: input.price.as_ref().map(BigDecimal::to_string),
+            price2: input.price2.as_ref().map(BigDecimal::to_string),
+            leverage: input.leverage.clone(),
+            userref: input.userref,
+            oflags: input.oflags.clone(),
+            validate: input.validate,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AddOrderResponse {
+    pub error: Vec<String>,
+    pub result: Option<AddOrderResult>,
+}
+
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AddOrderResult {
+    pub descr: OrderDescription,
+    /// The IDs of the placed orders. Empty when only validating.
+    #[serde(default)]
+    pub txid: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct OrderDescription {
+    pub order: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close: Option<String>,
+}
+
+// The parts of an OpenOrders or ClosedOrders response needed to
+// find out whether an order with a given userref was placed.
+#[derive(Deserialize)]
+pub(crate) struct OrdersByUserRefResponse {
+    pub result: Option<OrdersByUserRef>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct OrdersByUserRef {
+    #[serde(alias = "closed")]
+    pub open: HashMap<String, PlacedOrder>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PlacedOrder {
+    pub descr: OrderDescription,
+}
+
+impl OrdersByUserRefResponse {
+    /// Describes the orders found as if they had just been placed.
+    pub fn into_add_order_response(self) -> Option<AddOrderResponse> {
+        let orders = self.result?.open;
+        let descr = orders.values().next()?.descr.clone();
+        Some(AddOrderResponse {
+            error: Vec::new(),
+            result: Some(AddOrderResult {
+                descr,
+                txid: orders.into_keys().collect(),
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AddOrderInput, OrderSide, OrderType, SerializableAddOrderInput};
+    use bigdecimal::BigDecimal;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn serializes_kraken_field_names() {
+        let input = AddOrderInput {
+            pair: "XBTUSD".to_owned(),
+            side: OrderSide::Buy,
+            order_type: OrderType::StopLossLimit,
+            volume: BigDecimal::from_str("1.25").unwrap(),
+            price: Some(BigDecimal::from_str("27500").unwrap()),
+            price2: Some(BigDecimal::from_str("27400.5").unwrap()),
+            leverage: None,
+            userref: Some(42),
+            oflags: None,
+            validate: None,
+        };
+        let input = SerializableAddOrderInput::new("1".to_owned(), &input);
+        let expected = json!({
+            "nonce": "1",
+            "pair": "XBTUSD",
+            "type": "buy",
+            "ordertype": "stop-loss-limit",
+            "volume": "1.25",
+            "price": "27500",
+            "price2": "27400.5",
+            "userref": 42,
+        });
+        assert_eq!(serde_json::to_value(input).unwrap(), expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_serde() {
+        let input = AddOrderInput {
+            pair: "XBTUSD".to_owned(),
+            side: OrderSide::Sell,
+            order_type: OrderType::Limit,
+            volume: BigDecimal::from_str("0.50").unwrap(),
+            price: Some(BigDecimal::from_str("30000.1").unwrap()),
+            price2: None,
+            leverage: Some("2:1".to_owned()),
+            userref: None,
+            oflags: Some("post".to_owned()),
+            validate: Some(true),
+        };
+        let value = serde_json::to_value(&input).unwrap();
+        assert_eq!(value["volume"], json!("0.50"));
+        assert_eq!(value["order_type"], json!("limit"));
+        let input: AddOrderInput = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&input).unwrap(), value);
+    }
+}
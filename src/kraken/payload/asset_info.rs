@@ -6,24 +6,47 @@ use std::fmt;
 pub struct AssetInfoInput {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub asset: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "aclass", skip_serializing_if = "Option::is_none")]
     pub asset_class: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct AssetInfoResponse {
-    error: Vec<String>,
-    result: HashMap<String, AssetInfo>,
+    pub error: Vec<String>,
+    #[serde(default)]
+    pub result: HashMap<String, AssetInfo>,
+}
+
+/// AssetStatus is whether an asset can be deposited and withdrawn.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum AssetStatus {
+    Enabled,
+    DepositOnly,
+    WithdrawalOnly,
+    FundingTemporarilyDisabled,
+    /// A status this crate doesn't know about yet.
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct AssetInfo {
+    /// The asset class, such as "currency".
     #[serde(rename = "aclass")]
-    asset_class: String,
+    pub asset_class: String,
     #[serde(rename = "altname")]
-    alt_name: String,
-    decimals: u64,
-    display_decimals: u64,
+    pub alt_name: String,
+    /// The decimals the asset is recorded to.
+    pub decimals: u64,
+    /// The decimals the asset is usually displayed with.
+    pub display_decimals: u64,
+    /// The fraction of the asset's value which counts as margin collateral.
+    pub collateral_value: Option<f64>,
+    pub status: Option<AssetStatus>,
 }
 
 impl fmt::Display for AssetInfoResponse {
@@ -1,9 +1,13 @@
+use crate::kraken::Asset;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 pub struct AssetPairsInput {
     pub pairs: Vec<String>,
     pub info: Option<AssetPairsInfo>,
+    pub country_code: Option<String>,
 }
 
 impl From<AssetPairsInput> for SerializableAssetPairsInput {
@@ -14,19 +18,25 @@ impl From<AssetPairsInput> for SerializableAssetPairsInput {
             _ => Some(input.pairs.join(",")),
         };
 
-        Self { info, pairs }
+        Self {
+            info,
+            pairs,
+            country_code: input.country_code,
+        }
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SerializableAssetPairsInput {
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "pair", skip_serializing_if = "Option::is_none")]
     pub pairs: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub info: Option<AssetPairsInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum AssetPairsInfo {
     Info,
@@ -35,17 +45,73 @@ pub enum AssetPairsInfo {
     Margin,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct AssetPairsResponse {
+/// AssetPairsShape is implemented by the types each kind of AssetPairs
+/// response decodes into, since Kraken only returns some of a pair's
+/// fields unless all of its info is asked for.
+pub trait AssetPairsShape: DeserializeOwned {
+    const INFO: AssetPairsInfo;
+}
+
+impl AssetPairsShape for AssetPairInfo {
+    const INFO: AssetPairsInfo = AssetPairsInfo::Info;
+}
+
+impl AssetPairsShape for PairLeverage {
+    const INFO: AssetPairsInfo = AssetPairsInfo::Leverage;
+}
+
+impl AssetPairsShape for PairFees {
+    const INFO: AssetPairsInfo = AssetPairsInfo::Fees;
+}
+
+impl AssetPairsShape for PairMargin {
+    const INFO: AssetPairsInfo = AssetPairsInfo::Margin;
+}
+
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AssetPairsResponse<T = AssetPairInfo> {
     pub error: Vec<String>,
-    pub result: Option<HashMap<String, AssetPairInfo>>,
+    pub result: Option<HashMap<String, T>>,
+}
+
+/// PairStatus is whether a pair can be traded.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum PairStatus {
+    Online,
+    CancelOnly,
+    PostOnly,
+    LimitOnly,
+    ReduceOnly,
+    /// A status this crate doesn't know about yet.
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl fmt::Display for PairStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let val = match self {
+            Self::Online => "online",
+            Self::CancelOnly => "cancel_only",
+            Self::PostOnly => "post_only",
+            Self::LimitOnly => "limit_only",
+            Self::ReduceOnly => "reduce_only",
+            Self::Unknown => "unknown",
+        };
+        write!(f, "{}", val)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct AssetPairInfo {
     #[serde(rename = "altname")]
     pub alt_name: String,
-    #[serde(rename = "wsname")]
+    /// The name used by the websocket API. Pairs
+    /// which can't be traded over it have none.
+    #[serde(rename = "wsname", default)]
     pub websocket_name: String,
     #[serde(rename = "aclass_base")]
     pub base_component_asset_class: String,
@@ -54,16 +120,161 @@ pub struct AssetPairInfo {
     pub quote_component_asset_class: String,
     pub quote: String,
     pub pair_decimals: u64,
+    pub cost_decimals: Option<u64>,
     pub lot_decimals: u64,
     pub lot_multiplier: u64,
     pub leverage_buy: Vec<u64>,
     pub leverage_sell: Vec<u64>,
     pub fees: Vec<(u64, f64)>,
-    #[serde(rename = "fees_maker")]
+    #[serde(rename = "fees_maker", default)]
     pub maker_fees: Vec<(u64, f64)>,
     pub fee_volume_currency: String,
-    pub margin_call: u64,
-    pub margin_stop: u64,
+    /// The margin level, in percent, at which a margin call is made.
+    pub margin_call: f64,
+    /// The margin level, in percent, at which positions are liquidated.
+    pub margin_stop: f64,
+    /// The margin level, in percent, required to open a position.
+    pub margin_level: Option<f64>,
     #[serde(rename = "ordermin")]
     pub order_minimum: String,
+    /// The minimum cost of an order, in the quote asset.
+    #[serde(rename = "costmin")]
+    pub cost_minimum: Option<String>,
+    /// The smallest step the price can move by.
+    pub tick_size: Option<String>,
+    pub status: Option<PairStatus>,
+    /// The most of the base asset a long margin position may hold.
+    pub long_position_limit: Option<u64>,
+    /// The most of the base asset a short margin position may hold.
+    pub short_position_limit: Option<u64>,
+}
+
+/// PairLeverage is what the AssetPairs endpoint returns
+/// for each pair when asked for `info=leverage`.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PairLeverage {
+    pub leverage_buy: Vec<u64>,
+    pub leverage_sell: Vec<u64>,
+}
+
+/// PairFees is what the AssetPairs endpoint returns
+/// for each pair when asked for `info=fees`.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PairFees {
+    pub fees: Vec<(u64, f64)>,
+    #[serde(rename = "fees_maker", default)]
+    pub maker_fees: Vec<(u64, f64)>,
+    pub fee_volume_currency: String,
+}
+
+/// PairMargin is what the AssetPairs endpoint returns
+/// for each pair when asked for `info=margin`.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PairMargin {
+    pub margin_call: f64,
+    pub margin_stop: f64,
+    pub margin_level: Option<f64>,
+}
+
+impl AssetPairInfo {
+    /// Returns the asset bought or sold, such as BTC for "XXBTZUSD".
+    pub fn base_asset(&self) -> Asset {
+        Asset::from_kraken(&self.base)
+    }
+
+    /// Returns the asset prices are quoted in, such as USD for "XXBTZUSD".
+    pub fn quote_asset(&self) -> Asset {
+        Asset::from_kraken(&self.quote)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AssetPairInfo, AssetPairsInfo, AssetPairsInput, AssetPairsResponse, PairLeverage,
+        PairMargin, PairStatus, SerializableAssetPairsInput,
+    };
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn query_encodes_pairs_as_pair() {
+        let input = SerializableAssetPairsInput::from(AssetPairsInput {
+            pairs: vec!["XBTUSD".to_owned(), "ETHUSD".to_owned()],
+            info: Some(AssetPairsInfo::Leverage),
+            country_code: None,
+        });
+        let req = reqwest::Client::new()
+            .get("https://api.kraken.com/0/public/AssetPairs")
+            .query(&input)
+            .build()
+            .unwrap();
+        assert_eq!(
+            req.url().query(),
+            Some("pair=XBTUSD%2CETHUSD&info=leverage")
+        );
+    }
+
+    #[test]
+    fn parses_full_pair_info() {
+        let resp: AssetPairsResponse = serde_json::from_value(json!({
+            "error": [],
+            "result": {
+                "XXBTZUSD": {
+                    "altname": "XBTUSD",
+                    "wsname": "XBT/USD",
+                    "aclass_base": "currency",
+                    "base": "XXBT",
+                    "aclass_quote": "currency",
+                    "quote": "ZUSD",
+                    "lot": "unit",
+                    "cost_decimals": 5,
+                    "pair_decimals": 1,
+                    "lot_decimals": 8,
+                    "lot_multiplier": 1,
+                    "leverage_buy": [2, 3, 4, 5],
+                    "leverage_sell": [2, 3, 4, 5],
+                    "fees": [[0, 0.26], [50000, 0.24]],
+                    "fees_maker": [[0, 0.16], [50000, 0.14]],
+                    "fee_volume_currency": "ZUSD",
+                    "margin_call": 80,
+                    "margin_stop": 40,
+                    "ordermin": "0.0001",
+                    "costmin": "0.5",
+                    "tick_size": "0.1",
+                    "status": "online",
+                    "long_position_limit": 270,
+                    "short_position_limit": 180
+                }
+            }
+        }))
+        .unwrap();
+        let info: &AssetPairInfo = &resp.result.as_ref().unwrap()["XXBTZUSD"];
+        assert_eq!(info.status, Some(PairStatus::Online));
+        assert_eq!(info.tick_size.as_deref(), Some("0.1"));
+        assert_eq!(info.margin_call, 80.0);
+        assert_eq!(info.long_position_limit, Some(270));
+        assert_eq!(info.base_asset().ticker(), "BTC");
+    }
+
+    #[test]
+    fn parses_partial_pair_info() {
+        let resp: AssetPairsResponse<PairLeverage> = serde_json::from_value(json!({
+            "error": [],
+            "result": {"XETHZUSD": {"leverage_buy": [2, 3], "leverage_sell": [2]}}
+        }))
+        .unwrap();
+        assert_eq!(resp.result.unwrap()["XETHZUSD"].leverage_sell, vec![2]);
+        let resp: AssetPairsResponse<PairMargin> = serde_json::from_value(json!({
+            "error": [],
+            "result": {"XETHZUSD": {"margin_call": 80, "margin_stop": 40, "margin_level": 20}}
+        }))
+        .unwrap();
+        assert_eq!(resp.result.unwrap()["XETHZUSD"].margin_level, Some(20.0));
+        let status: PairStatus = serde_json::from_value(json!("delisted")).unwrap();
+        assert_eq!(status, PairStatus::Unknown);
+    }
 }
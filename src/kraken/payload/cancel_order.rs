@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct CancelOrderInput {
+    pub nonce: String,
+    /// The transaction ID or userref of the orders to cancel.
+    pub txid: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CancelOrderResponse {
+    pub error: Vec<String>,
+    pub result: Option<CancelOrderResult>,
+}
+
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CancelOrderResult {
+    /// The number of orders cancelled.
+    pub count: u32,
+    /// True if the cancellation is still pending.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<bool>,
+}
@@ -0,0 +1,177 @@
+// Decodes every response in tests/fixtures through each payload type for
+// its endpoint. Fixtures live in a directory named after the endpoint, so
+// saving a response Kraken gave there is enough to have it checked.
+use super::{
+    kraken_errors, AccountBalanceResponse, AddOrderResponse, AssetInfoResponse, AssetPairsResponse,
+    CancelOrderResponse, OrdersByUserRefResponse, PairFees, PairLeverage, PairMargin,
+    RawRecentSpreadsResponse, RawTickerResponse, RecentSpreadsResponse, ServerTimeResponse,
+    SystemStatusResponse, TickerResponse,
+};
+use crate::kraken::{Asset, AssetNormalizer, OrderValidator, PairRegistry};
+use pretty_assertions::assert_eq;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::PathBuf;
+
+// The errors a payload decoded, or why it couldn't be decoded.
+type Decoded = Result<Vec<String>, String>;
+
+fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(body).map_err(|err| err.to_string())
+}
+
+// Runs `check` on every fixture of the endpoint. It must return exactly the
+// errors in the body, so that Kraken's errors always reach the caller, or
+// fail for fixtures named "malformed*", which Kraken should never send.
+fn conforms<F: Fn(&[u8]) -> Decoded>(endpoint: &str, check: F) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(endpoint);
+    let mut fixtures: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures in {}", dir.display());
+    let failures: Vec<String> = fixtures
+        .iter()
+        .filter_map(|path| {
+            let body = fs::read(path).unwrap();
+            let malformed = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("malformed"));
+            let failure = match check(&body) {
+                Ok(_) if malformed => "decoded a malformed body".to_owned(),
+                Err(_) if malformed => return None,
+                Ok(errors) if errors == kraken_errors(&body) => return None,
+                Ok(errors) => format!("decoded the errors {:?}", errors),
+                Err(err) => err,
+            };
+            Some(format!("{}: {}", path.display(), failure))
+        })
+        .collect();
+    assert_eq!(failures, Vec::<String>::new());
+}
+
+#[test]
+fn server_time() {
+    conforms("Time", |body| {
+        decode::<ServerTimeResponse>(body).map(|r| r.error)
+    });
+}
+
+#[test]
+fn system_status() {
+    conforms("SystemStatus", |body| {
+        decode::<SystemStatusResponse>(body).map(|r| r.error)
+    });
+}
+
+#[test]
+fn asset_info() {
+    conforms("Assets", |body| {
+        let resp = decode::<AssetInfoResponse>(body)?;
+        // What the response teaches must agree with the built-in table.
+        let normalizer = AssetNormalizer::from_asset_info(&resp);
+        for name in resp.result.keys() {
+            let asset = normalizer.normalize(name);
+            if asset != Asset::from_kraken(name) || normalizer.kraken_name(&asset) != *name {
+                return Err(format!("{} normalized to {}", name, asset));
+            }
+        }
+        Ok(resp.error)
+    });
+}
+
+#[test]
+fn asset_pairs() {
+    conforms("AssetPairs", |body| {
+        decode::<AssetPairsResponse<PairLeverage>>(body)?;
+        decode::<AssetPairsResponse<PairFees>>(body)?;
+        decode::<AssetPairsResponse<PairMargin>>(body)?;
+        let resp = decode::<AssetPairsResponse>(body)?;
+        if let Some(pairs) = resp.result {
+            pairs
+                .values()
+                .for_each(|info| drop(OrderValidator::new(info)));
+            let count = pairs.len();
+            if PairRegistry::new(pairs).len() != count {
+                return Err("a pair name was rejected".to_owned());
+            }
+        }
+        Ok(resp.error)
+    });
+}
+
+#[test]
+fn ticker() {
+    conforms("Ticker", |body| {
+        let raw = decode::<RawTickerResponse>(body)?;
+        let resp = TickerResponse::from(raw);
+        if !resp.skipped.is_empty() {
+            return Err(format!("skipped tickers: {:?}", resp.skipped));
+        }
+        Ok(resp.error)
+    });
+}
+
+#[test]
+fn recent_spreads() {
+    conforms("Spread", |body| {
+        let raw = decode::<RawRecentSpreadsResponse>(body)?;
+        let resp = RecentSpreadsResponse::try_from(raw).map_err(|err| err.to_string())?;
+        Ok(resp.error)
+    });
+}
+
+#[test]
+fn account_balance() {
+    conforms("Balance", |body| {
+        let resp = decode::<AccountBalanceResponse>(body)?;
+        // Two names normalizing to the same asset would lose a balance.
+        let raw = decode::<Value>(body)?;
+        let names = raw["result"].as_object().map_or(0, |result| result.len());
+        if resp.result.len() != names {
+            return Err(format!(
+                "{} balances decoded as {}",
+                names,
+                resp.result.len()
+            ));
+        }
+        Ok(resp.error)
+    });
+}
+
+#[test]
+fn add_order() {
+    conforms("AddOrder", |body| {
+        decode::<AddOrderResponse>(body).map(|r| r.error)
+    });
+}
+
+#[test]
+fn cancel_order() {
+    conforms("CancelOrder", |body| {
+        decode::<CancelOrderResponse>(body).map(|r| r.error)
+    });
+}
+
+// OpenOrders and ClosedOrders are only decoded to find orders by userref,
+// by a payload which leaves out the errors.
+#[test]
+fn open_orders() {
+    conforms("OpenOrders", |body| {
+        decode::<OrdersByUserRefResponse>(body)?.into_add_order_response();
+        Ok(kraken_errors(body))
+    });
+}
+
+#[test]
+fn closed_orders() {
+    conforms("ClosedOrders", |body| {
+        decode::<OrdersByUserRefResponse>(body)?.into_add_order_response();
+        Ok(kraken_errors(body))
+    });
+}
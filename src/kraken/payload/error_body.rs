@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+// Every Kraken response carries an "error" array, whatever the shape of
+// its result. Kraken Futures sends a single string instead, if any.
+#[derive(Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    error: Option<Errors>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Errors {
+    Spot(Vec<String>),
+    Futures(String),
+}
+
+/// Returns the error codes in a response body, such as
+/// "EOrder:Insufficient funds", or none if it isn't Kraken's JSON.
+pub(crate) fn kraken_errors(body: &[u8]) -> Vec<String> {
+    match serde_json::from_slice::<ErrorBody>(body) {
+        Ok(ErrorBody {
+            error: Some(Errors::Spot(errors)),
+        }) => errors,
+        Ok(ErrorBody {
+            error: Some(Errors::Futures(error)),
+        }) => vec![error],
+        _ => Vec::new(),
+    }
+}
@@ -1,9 +1,18 @@
 pub use account_balance::{AccountBalanceInput, AccountBalanceResponse};
-pub use asset_info::{AssetInfoInput, AssetInfoResponse};
+pub(crate) use add_order::OrdersByUserRefResponse;
+pub use add_order::{
+    AddOrderInput, AddOrderResponse, AddOrderResult, OrderDescription, OrderSide, OrderType,
+    SerializableAddOrderInput,
+};
+pub use asset_info::{AssetInfo, AssetInfoInput, AssetInfoResponse, AssetStatus};
 pub use asset_pairs::{
-    AssetPairsInfo, AssetPairsInput, AssetPairsResponse, SerializableAssetPairsInput,
+    AssetPairInfo, AssetPairsInfo, AssetPairsInput, AssetPairsResponse, AssetPairsShape, PairFees,
+    PairLeverage, PairMargin, PairStatus, SerializableAssetPairsInput,
 };
+pub use cancel_order::{CancelOrderInput, CancelOrderResponse, CancelOrderResult};
+pub(crate) use error_body::kraken_errors;
 pub use open_orders::OpenOrdersInput;
+pub(crate) use raw_input::RawPrivateInput;
 pub use recent_spreads::{
     RawRecentSpreadsResponse, RecentSpreadsInput, RecentSpreadsResponse, Spread,
 };
@@ -13,9 +22,15 @@ pub use ticker::{RawTickerResponse, TickerInfo, TickerInput, TickerResponse};
 pub use trade_balance::TradeBalanceInput;
 
 mod account_balance;
+mod add_order;
 mod asset_info;
 mod asset_pairs;
+mod cancel_order;
+#[cfg(test)]
+mod conformance;
+mod error_body;
 mod open_orders;
+mod raw_input;
 mod recent_spreads;
 mod server_time;
 mod system_status;
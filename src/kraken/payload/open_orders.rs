@@ -5,6 +5,6 @@ pub struct OpenOrdersInput {
     pub nonce: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trades: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub user_ref: Option<u32>,
+    #[serde(rename = "userref", skip_serializing_if = "Option::is_none")]
+    pub user_ref: Option<i32>,
 }
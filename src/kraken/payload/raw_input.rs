@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+// Adds the nonce to the caller's parameters for a private endpoint
+// which has no bindings, since every private request must carry one.
+#[derive(Serialize)]
+pub(crate) struct RawPrivateInput<'a, P> {
+    pub nonce: String,
+    #[serde(flatten)]
+    pub params: &'a P,
+}
@@ -1,6 +1,8 @@
+use crate::kraken::ClientError;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt;
 
 #[derive(Serialize, Deserialize)]
@@ -11,15 +13,21 @@ pub struct RecentSpreadsInput {
 }
 
 ///////////////////////////////////////////
-//// User-facing Types for Client /////////
+// User-facing Types for Client ///////////
 ///////////////////////////////////////////
 
+/// RecentSpreadsResponse holds the spreads Kraken returned. With the
+/// `serde` feature it serializes as `{"error": [...], "result": [Spread]}`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RecentSpreadsResponse {
     pub error: Vec<String>,
     pub result: Option<Vec<Spread>>,
 }
 
+/// Spread is the best bid and ask of a pair at a time, in Unix seconds.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Spread {
     pub pair: String,
     pub time: u64,
@@ -27,44 +35,39 @@ pub struct Spread {
     pub ask: String,
 }
 
-fn strongly_type_hashmap(mut hash: HashMap<String, Value>) -> Vec<Spread> {
+// Converts each pair's list of [time, bid, ask] spreads.
+fn strongly_type_hashmap(
+    mut hash: HashMap<String, Value>,
+) -> Result<Vec<Spread>, serde_json::Error> {
     // There's one key in this map unlike the others. Remove it.
-    let _last_id = hash.remove("last").unwrap().as_u64().unwrap();
-    hash.iter().map(parse_spread_json).flatten().collect()
+    hash.remove("last");
+    let mut spreads = Vec::new();
+    for (pair, val) in hash {
+        let rows: Vec<(u64, String, String)> = serde_json::from_value(val)?;
+        spreads.extend(rows.into_iter().map(|(time, bid, ask)| Spread {
+            pair: pair.clone(),
+            time,
+            bid,
+            ask,
+        }));
+    }
+    Ok(spreads)
 }
 
-fn parse_spread_json(args: (&String, &Value)) -> Vec<Spread> {
-    let (key, val) = args;
-    let pair = key.to_string();
-    let arr = val
-        .as_array()
-        .expect("Value received does not meet API specification.");
+impl TryFrom<RawRecentSpreadsResponse> for RecentSpreadsResponse {
+    type Error = ClientError;
 
-    // arr is a list of spreads. Each spread is an array itself.
-    arr.iter()
-        .map(|elem| {
-            let pair = pair.clone();
-            let time = elem.get(0).unwrap().as_u64().unwrap();
-            let bid = elem.get(1).unwrap().as_str().unwrap().to_string();
-            let ask = elem.get(2).unwrap().as_str().unwrap().to_string();
-            Spread {
-                pair,
-                time,
-                ask,
-                bid,
-            }
+    /// Fails with ClientError::Decode if a spread isn't a [time, bid, ask] array.
+    fn try_from(raw: RawRecentSpreadsResponse) -> Result<Self, Self::Error> {
+        let result = raw
+            .result
+            .map(strongly_type_hashmap)
+            .transpose()
+            .map_err(ClientError::Decode)?;
+        Ok(Self {
+            error: raw.error,
+            result,
         })
-        .collect()
-}
-
-// TODO: This should be converted into a FromStr implementation.
-impl From<RawRecentSpreadsResponse> for RecentSpreadsResponse {
-    fn from(raw: RawRecentSpreadsResponse) -> Self {
-        let error = raw.error;
-        // Iterate over the HashMap, converting each
-        // key/value into a Vector of Spreads.
-        let result = raw.result.map(strongly_type_hashmap);
-        Self { error, result }
     }
 }
 
@@ -80,7 +83,7 @@ impl fmt::Display for RecentSpreadsResponse {
 }
 
 ///////////////////////////////////////////
-//// Raw Types for accepting JSON /////////
+// Raw Types for accepting JSON ///////////
 ///////////////////////////////////////////
 
 #[derive(Serialize, Deserialize)]
@@ -99,3 +102,36 @@ impl fmt::Display for RawRecentSpreadsResponse {
         write!(f, "{}", val)
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::{RawRecentSpreadsResponse, RecentSpreadsResponse};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn round_trips_through_serde() {
+        let raw: RawRecentSpreadsResponse = serde_json::from_value(json!({
+            "error": [],
+            "result": {
+                "XXBTZUSD": [[1688671834, "30000.00000", "30000.10000"]],
+                "last": 1688671834
+            }
+        }))
+        .unwrap();
+        let value = serde_json::to_value(RecentSpreadsResponse::try_from(raw).unwrap()).unwrap();
+        let expected = json!({
+            "error": [],
+            "result": [{
+                "pair": "XXBTZUSD",
+                "time": 1688671834,
+                "bid": "30000.00000",
+                "ask": "30000.10000"
+            }]
+        });
+        assert_eq!(value, expected);
+        let resp: RecentSpreadsResponse = serde_json::from_value(value).unwrap();
+        assert_eq!(serde_json::to_value(resp).unwrap(), expected);
+    }
+}
@@ -1,19 +1,25 @@
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use std::fmt;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ServerTimeResponse {
     pub error: Vec<String>,
-    pub result: ServerTimeResult,
+    /// The time, or None if Kraken returned errors instead.
+    pub result: Option<ServerTimeResult>,
 }
 
 impl fmt::Display for ServerTimeResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", &self.result.rfc1123)
+        match &self.result {
+            Some(result) => write!(f, "{}", &result.rfc1123),
+            None => write!(f, "{:#?}", &self.error),
+        }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ServerTimeResult {
     #[serde(rename = "unixtime")]
     pub unix_time: u64,
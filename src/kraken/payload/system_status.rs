@@ -1,23 +1,25 @@
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use std::fmt;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SystemStatusResponse {
-    pub result: SystemStatusResult,
+    /// The status, or None if Kraken returned errors instead.
+    pub result: Option<SystemStatusResult>,
     pub error: Vec<String>,
 }
 
 impl fmt::Display for SystemStatusResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if !self.error.is_empty() {
-            write!(f, "{:#?}", &self.error)
-        } else {
-            write!(f, "{}", &self.result)
+        match &self.result {
+            Some(result) if self.error.is_empty() => write!(f, "{}", result),
+            _ => write!(f, "{:#?}", &self.error),
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SystemStatusResult {
     pub timestamp: String,
     pub status: SystemStatusEnum,
@@ -29,7 +31,8 @@ impl fmt::Display for SystemStatusResult {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "snake_case")]
 pub enum SystemStatusEnum {
     Online,
@@ -3,6 +3,7 @@ use bigdecimal::BigDecimal;
 use serde_json::Value;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BidInfo {
     pub bid: BigDecimal,
     pub whole_lot_volume: BigDecimal,
@@ -3,6 +3,7 @@ use bigdecimal::BigDecimal;
 use serde_json::Value;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HighInfo {
     pub today: BigDecimal,
     pub rolling_24h: BigDecimal,
@@ -2,6 +2,7 @@ use super::util::{ArrayWrapper, NumTradesError};
 use serde_json::Value;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NumTradesInfo {
     //pub daily_volume: u64,
     //pub rolling_24h_volume: u64,
@@ -1,8 +1,14 @@
+use bigdecimal::BigDecimal;
+
 use super::{
     AskInfo, BidInfo, HighInfo, LastTradeInfo, LowInfo, NumTradesInfo, VWAInfo, VolumeInfo,
 };
 
+/// TickerInfo is Kraken's ticker for one pair. With the `serde` feature
+/// it serializes as an object keyed by these field names, rather than
+/// Kraken's one-letter arrays, with every decimal written as a string.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TickerInfo {
     pub ticker: String,
     pub ask: AskInfo,
@@ -13,6 +19,6 @@ pub struct TickerInfo {
     pub num_trades: NumTradesInfo,
     pub volume: VolumeInfo,
     pub vol_weighted_avg: VWAInfo,
-    // TODO: Revisit capturing this value.
-    // pub opening_price: BigDecimal,
+    /// Today's opening price.
+    pub opening_price: BigDecimal,
 }
@@ -2,5 +2,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
 pub struct TickerInput {
-    pub pair: String,
+    /// Comma separated pairs, or None for every pair.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pair: Option<String>,
 }
@@ -1,58 +1,119 @@
+use super::util::{try_from_value, OpeningPriceError};
 use super::{
     AskInfo, BidInfo, HighInfo, LastTradeInfo, LowInfo, NumTradesInfo, RawTickerResponse,
     TickerInfo, VWAInfo, VolumeInfo,
 };
+use crate::kraken::{trace, AssetPair};
+use bigdecimal::BigDecimal;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::error::Error;
 
+/// TickerResponse holds the tickers Kraken returned. With the `serde`
+/// feature it serializes as `{"error": [...], "result": {pair: TickerInfo}}`,
+/// plus the `skipped` tickers if there are any.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TickerResponse {
     pub error: Vec<String>,
-    pub result: Option<Vec<TickerInfo>>,
+    /// The ticker of each pair, keyed by the name Kraken gives the pair.
+    pub result: Option<HashMap<AssetPair, TickerInfo>>,
+    /// The tickers which couldn't be parsed, keyed by the name Kraken
+    /// gives the pair, with the reason each one was left out of `result`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub skipped: HashMap<String, String>,
 }
 
-impl TryFrom<RawTickerResponse> for TickerResponse {
-    type Error = Box<dyn Error>;
-    fn try_from(raw: RawTickerResponse) -> Result<Self, Self::Error> {
-        // First, let's copy over any errors.
-        let error = raw.error;
-        // Next, let's parse the body.
-        if raw.result.is_none() {
-            return Ok(Self {
-                error,
-                result: None,
-            });
+impl From<RawTickerResponse> for TickerResponse {
+    /// Converts each ticker Kraken returned. A ticker which can't be
+    /// parsed is moved to `skipped` with the parse error, so that one
+    /// malformed pair doesn't lose the others.
+    fn from(raw: RawTickerResponse) -> Self {
+        let mut skipped = HashMap::new();
+        let result = raw.result.map(|hash| {
+            hash.into_iter()
+                .filter_map(|(name, v)| match parse_ticker(name.clone(), &v) {
+                    Ok(ticker) => Some(ticker),
+                    Err(err) => {
+                        trace::warning!(pair = %name, error = %err, "skipping unparseable ticker");
+                        skipped.insert(name, err.to_string());
+                        None
+                    }
+                })
+                .collect()
+        });
+        Self {
+            error: raw.error,
+            result,
+            skipped,
         }
-        let mut result = Vec::new();
-        let hash = raw.result.unwrap();
-        // Extract each of the Info types.
-        for (k, v) in hash {
-            let ticker = k;
-            let ask = AskInfo::try_from(&v)?;
-            let bid = BidInfo::try_from(&v)?;
-            let low = LowInfo::try_from(&v)?;
-            let high = HighInfo::try_from(&v)?;
-            let volume = VolumeInfo::try_from(&v)?;
-            let vol_weighted_avg = VWAInfo::try_from(&v)?;
-            let num_trades = NumTradesInfo::try_from(&v)?;
-            let last_trade_closed = LastTradeInfo::try_from(&v)?;
+    }
+}
 
-            let info = TickerInfo {
-                ticker,
-                ask,
-                bid,
-                high,
-                low,
-                last_trade_closed,
-                num_trades,
-                volume,
-                vol_weighted_avg,
-            };
-            result.push(info)
-        }
-        Ok(TickerResponse {
-            error,
-            result: Some(result),
-        })
+fn parse_ticker(ticker: String, v: &Value) -> Result<(AssetPair, TickerInfo), Box<dyn Error>> {
+    let pair = ticker.parse()?;
+    let info = TickerInfo {
+        ticker,
+        ask: AskInfo::try_from(v)?,
+        bid: BidInfo::try_from(v)?,
+        high: HighInfo::try_from(v)?,
+        low: LowInfo::try_from(v)?,
+        last_trade_closed: LastTradeInfo::try_from(v)?,
+        num_trades: NumTradesInfo::try_from(v)?,
+        volume: VolumeInfo::try_from(v)?,
+        vol_weighted_avg: VWAInfo::try_from(v)?,
+        opening_price: opening_price(v)?,
+    };
+    Ok((pair, info))
+}
+
+// The opening price is a single decimal rather than an array.
+fn opening_price(val: &Value) -> Result<BigDecimal, OpeningPriceError> {
+    try_from_value(val)
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::{RawTickerResponse, TickerResponse};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_through_serde() {
+        let raw: RawTickerResponse = serde_json::from_value(json!({
+            "error": [],
+            "result": {
+                "XXBTZUSD": {
+                    "a": ["30000.10", "1", "1.000"],
+                    "b": ["30000.00", "2", "2.000"],
+                    "c": ["30000.10", "0.1"],
+                    "v": ["100.1", "200.2"],
+                    "p": ["29950.5", "29900.2"],
+                    "t": [1000, 2000],
+                    "l": ["29500.0", "29400.0"],
+                    "h": ["30100.0", "30200.0"],
+                    "o": "29800.0"
+                }
+            }
+        }))
+        .unwrap();
+        let resp = TickerResponse::from(raw);
+        let value = serde_json::to_value(&resp).unwrap();
+        let info = &value["result"]["XXBTZUSD"];
+        assert_eq!(
+            info["ask"],
+            json!({"ask": "30000.10", "whole_lot_volume": "1", "lot_volume": "1.000"})
+        );
+        assert_eq!(
+            info["num_trades"],
+            json!({"daily_num_trades": 1000, "rolling_24h_num_trades": 2000})
+        );
+        assert_eq!(info["opening_price"], json!("29800.0"));
+        let resp: TickerResponse = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&resp).unwrap(), value);
     }
 }
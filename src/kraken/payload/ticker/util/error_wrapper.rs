@@ -106,6 +106,17 @@ impl ErrorWrapper for VolumeInfoMetadata {
     }
 }
 
+pub struct OpeningPriceMetadata {}
+
+impl ErrorWrapper for OpeningPriceMetadata {
+    fn wrapper() -> &'static str {
+        "Error Parsing OpeningPrice"
+    }
+    fn key() -> &'static str {
+        "o"
+    }
+}
+
 pub struct VWAInfoMetadata {}
 
 impl ErrorWrapper for VWAInfoMetadata {
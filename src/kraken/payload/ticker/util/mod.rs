@@ -1,10 +1,10 @@
 pub use array_wrapper::ArrayWrapper;
 pub use error_wrapper::ErrorWrapper;
-pub use parse_error::ParseError;
 pub use parse_error::{
-    AskError, BidError, HighError, LastTradeError, LowError, NumTradesError, VWAError, VolumeError,
+    AskError, BidError, HighError, LastTradeError, LowError, NumTradesError, OpeningPriceError,
+    VWAError, VolumeError,
 };
-pub use unpack::unpack_decimal_array;
+pub use unpack::try_from_value;
 
 mod array_wrapper;
 mod error_wrapper;
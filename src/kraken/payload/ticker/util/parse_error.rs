@@ -8,6 +8,7 @@ pub type HighError = ParseError<HighInfoMetadata>;
 pub type LowError = ParseError<LowInfoMetadata>;
 pub type LastTradeError = ParseError<LastTradeInfoMetadata>;
 pub type NumTradesError = ParseError<NumTradesInfoMetadata>;
+pub type OpeningPriceError = ParseError<OpeningPriceMetadata>;
 pub type VolumeError = ParseError<VolumeInfoMetadata>;
 pub type VWAError = ParseError<VWAInfoMetadata>;
 
@@ -9,7 +9,6 @@ pub fn unpack_decimal_array<T: ErrorWrapper, const N: usize>(
 ) -> Result<Box<[BigDecimal; N]>, ParseError<T>> {
     let unpacker = |i| unpack_decimal(array.get(i));
     (0..N)
-        .into_iter()
         .map(unpacker)
         .collect::<Result<Vec<BigDecimal>, ParseError<T>>>()
         .map(vec_to_array)
@@ -20,7 +19,6 @@ pub fn unpack_u64_array<T: ErrorWrapper, const N: usize>(
 ) -> Result<Box<[u64; N]>, ParseError<T>> {
     let unpacker = |i| unpack_u64(array.get(i));
     (0..N)
-        .into_iter()
         .map(unpacker)
         .collect::<Result<Vec<u64>, ParseError<T>>>()
         .map(vec_to_array)
@@ -72,6 +70,17 @@ pub fn try_from_map_u64<T: ErrorWrapper, const N: usize>(
     }
 }
 
+// Unpacks a decimal which isn't wrapped in an array, like the opening price.
+pub fn try_from_value<T: ErrorWrapper>(val: &Value) -> Result<BigDecimal, ParseError<T>> {
+    let obj = val
+        .as_object()
+        .ok_or_else(ParseError::<T>::try_from_error)?;
+    match obj.get(T::key()) {
+        Some(decimal) => unpack_unwrapped_decimal(decimal),
+        None => Err(ParseError::<T>::no_key_error()),
+    }
+}
+
 fn vec_to_array<T, const N: usize>(v: Vec<T>) -> Box<[T; N]> {
     // else-case is what happens when the vector isn't the right length.
     let else_case = |v: Vec<T>| panic!("Expected a Vec of length {} but it was {}", N, v.len());
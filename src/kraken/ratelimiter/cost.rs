@@ -0,0 +1,30 @@
+use crate::kraken::endpoints::{
+    ADD_ORDER, CANCEL_ORDER, LEDGERS, QUERY_LEDGERS, QUERY_TRADES, TRADES_HISTORY,
+};
+
+/// Returns how many points a call to the endpoint adds to Kraken's REST
+/// call counter. Ledger and trade history queries cost 2 and every other
+/// call costs 1, except for placing and cancelling orders, which count
+/// against the per-pair trading counter instead.
+pub fn endpoint_cost(path: &str) -> usize {
+    match path {
+        LEDGERS | QUERY_LEDGERS | TRADES_HISTORY | QUERY_TRADES => 2,
+        ADD_ORDER | CANCEL_ORDER => 0,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::endpoint_cost;
+    use crate::kraken::endpoints::{ACCOUNT_BALANCE, ADD_ORDER, LEDGERS, TRADES_HISTORY};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn history_queries_cost_more() {
+        assert_eq!(endpoint_cost(ACCOUNT_BALANCE), 1);
+        assert_eq!(endpoint_cost(LEDGERS), 2);
+        assert_eq!(endpoint_cost(TRADES_HISTORY), 2);
+        assert_eq!(endpoint_cost(ADD_ORDER), 0);
+    }
+}
@@ -0,0 +1,194 @@
+use super::leaky_bucket::{BucketDescription, LeakyBucket};
+use super::RateLimiter;
+use crate::kraken::trace;
+use crate::kraken::AccountTier;
+use async_trait::async_trait;
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// FileRateLimiter is a leaky bucket whose state lives in a file, so that
+/// processes on one host sharing an API key draw from the same bucket.
+/// Each use takes an exclusive lock on the file, refills the bucket from
+/// the wall-clock time elapsed since it was last written, and writes it
+/// back before releasing the lock. If the file can't be used, requests
+/// are paced by an in-process LeakyBucket with the same limits instead,
+/// which throttles this process alone. The error is reported as a tracing
+/// event with the "tracing" feature.
+#[derive(Clone)]
+pub struct FileRateLimiter {
+    path: PathBuf,
+    config: BucketDescription,
+    fallback: Arc<LeakyBucket>,
+}
+
+// The bucket as stored in the file.
+struct FileState {
+    tokens: f64,
+    updated: Duration,
+}
+
+impl FileRateLimiter {
+    /// The file is created, holding a full bucket, if it does not exist.
+    pub fn new<P: AsRef<Path>>(path: P, tier: AccountTier) -> Self {
+        let config = LeakyBucket::bucket_configuration(tier);
+        Self {
+            path: path.as_ref().to_path_buf(),
+            config,
+            fallback: Arc::new(LeakyBucket::with_description(config)),
+        }
+    }
+
+    // Takes `count` tokens if there are enough, returning
+    // how long to wait before trying again if there aren't.
+    fn try_take(&self, count: f64) -> io::Result<Option<Duration>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        file.lock_exclusive()?;
+        let result = self.take_locked(&mut file, count);
+        file.unlock()?;
+        result
+    }
+
+    fn take_locked(&self, file: &mut File, count: f64) -> io::Result<Option<Duration>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut state = self.read(file)?.unwrap_or(FileState {
+            tokens: self.config.max_size,
+            updated: now,
+        });
+        state.tokens = self
+            .config
+            .refill(state.tokens, now.saturating_sub(state.updated));
+        state.updated = now;
+        let wait = if state.tokens >= count {
+            state.tokens -= count;
+            None
+        } else {
+            Some(self.config.wait(state.tokens, count))
+        };
+        Self::write(file, &state)?;
+        Ok(wait)
+    }
+
+    fn read(&self, file: &mut File) -> io::Result<Option<FileState>> {
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let mut fields = contents.split_whitespace().map(str::parse::<f64>);
+        match (fields.next(), fields.next()) {
+            (Some(Ok(tokens)), Some(Ok(updated))) => Ok(Some(FileState {
+                tokens,
+                updated: Duration::from_secs_f64(updated.max(0.0)),
+            })),
+            // A new or unreadable file starts over with a full bucket.
+            _ => Ok(None),
+        }
+    }
+
+    fn write(file: &mut File, state: &FileState) -> io::Result<()> {
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        let contents = format!("{} {}", state.tokens, state.updated.as_secs_f64());
+        file.write_all(contents.as_bytes())
+    }
+
+    fn read_remaining(&self) -> io::Result<usize> {
+        let mut file = OpenOptions::new().read(true).open(&self.path)?;
+        file.lock_shared()?;
+        let state = self.read(&mut file);
+        file.unlock()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(match state? {
+            Some(state) => {
+                let elapsed = now.saturating_sub(state.updated);
+                self.config.refill(state.tokens, elapsed) as usize
+            }
+            None => self.config.max_size as usize,
+        })
+    }
+}
+
+#[async_trait]
+impl RateLimiter for FileRateLimiter {
+    async fn use_rate_limit(&self, count: usize) {
+        let tokens = self.config.clamp(count);
+        loop {
+            let limiter = self.clone();
+            // Waiting for the lock blocks, so keep it off the runtime's threads.
+            let taken = tokio::task::spawn_blocking(move || limiter.try_take(tokens))
+                .await
+                .unwrap_or_else(|err| Err(io::Error::other(err)));
+            match taken {
+                Ok(None) => return,
+                Ok(Some(wait)) => {
+                    trace::debug!(
+                        tokens,
+                        wait_ms = wait.as_millis() as u64,
+                        "waiting for rate limit tokens"
+                    );
+                    tokio::time::sleep(wait).await
+                }
+                Err(_err) => {
+                    trace::warning!(
+                        path = ?self.path,
+                        error = %_err,
+                        "rate limit file is unusable, limiting this process alone"
+                    );
+                    return self.fallback.use_rate_limit(count).await;
+                }
+            }
+        }
+    }
+
+    async fn remaining(&self) -> usize {
+        let limiter = self.clone();
+        let remaining = tokio::task::spawn_blocking(move || limiter.read_remaining())
+            .await
+            .unwrap_or_else(|err| Err(io::Error::other(err)));
+        match remaining {
+            Ok(remaining) => remaining,
+            Err(_) => self.fallback.remaining().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileRateLimiter;
+    use crate::kraken::ratelimiter::RateLimiter;
+    use crate::kraken::AccountTier;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+
+    #[tokio::test]
+    async fn limiters_on_one_file_share_a_bucket() {
+        let path = std::env::temp_dir().join(format!("kraken-bucket-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let first = FileRateLimiter::new(&path, AccountTier::Starter);
+        let second = FileRateLimiter::new(&path, AccountTier::Starter);
+        first.use_rate_limit(10).await;
+        second.use_rate_limit(4).await;
+        assert_eq!(first.remaining().await, 1);
+        assert_eq!(second.remaining().await, 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_a_bucket_in_process() {
+        let dir = std::env::temp_dir().join(format!("kraken-missing-{}", std::process::id()));
+        let limiter = FileRateLimiter::new(dir.join("bucket"), AccountTier::Starter);
+        limiter.use_rate_limit(10).await;
+        assert_eq!(limiter.remaining().await, 5);
+        assert!(!dir.exists());
+    }
+}
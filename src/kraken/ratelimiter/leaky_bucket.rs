@@ -1,85 +1,185 @@
-#![allow(dead_code)]
-
+use super::RateLimiter;
+use crate::kraken::trace;
 use crate::kraken::AccountTier;
-use flume::{Receiver, TrySendError};
-use std::collections::HashMap;
+use async_trait::async_trait;
+use std::sync::Mutex;
 use std::time::Duration;
+use tokio::time::Instant;
 
 /// A LeakyBucket refers to a strategy for rate limiting
-/// where a channel of a fixed size is created, and elements
-/// are removed from that channels as requests are consumed.
-/// When the channel has been emptied, requests have hit their
-/// limit. The bucket refills by having a separate thread fill
-/// the channel on a given schedule.
+/// where a bucket of a fixed size holds one token per request,
+/// and a token is removed from the bucket as each request is made.
+/// When the bucket has been emptied, requests have hit their
+/// limit. The bucket refills at a steady rate, computed from the
+/// time elapsed whenever it is used, so no background task is needed
+/// and the bucket follows tokio's clock, including when it is paused.
 pub struct LeakyBucket {
-    recv: Receiver<()>,
+    config: BucketDescription,
+    state: Mutex<BucketState>,
+    // Held by the caller waiting for tokens, so waiters are served in order.
+    queue: tokio::sync::Mutex<()>,
+}
+
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
 }
 
 impl LeakyBucket {
     pub fn new(tier: AccountTier) -> Self {
-        let config = Self::bucket_configuration(tier);
-        let (sender, receiver) = flume::bounded(config.max_size.into());
-        // Fill the bucket, initializing the semaphore resources
-        for _ in 0..config.max_size {
-            sender.send(()).unwrap();
+        Self::with_description(Self::bucket_configuration(tier))
+    }
+
+    /// Returns a bucket for Kraken Futures, which allows the private
+    /// endpoints a cost of 500 every 10 seconds. FuturesClient uses
+    /// one by default, charging each call futures::endpoint_cost.
+    pub fn futures() -> Self {
+        Self::with_description(BucketDescription {
+            max_size: 500.0,
+            decay_per_sec: 50.0,
+        })
+    }
+
+    pub(super) fn with_description(config: BucketDescription) -> Self {
+        Self {
+            config,
+            // The bucket starts full.
+            state: Mutex::new(BucketState {
+                tokens: config.max_size,
+                updated: Instant::now(),
+            }),
+            queue: tokio::sync::Mutex::new(()),
         }
-        tokio::spawn(async move {
-            loop {
-                std::thread::sleep(config.fill_rate);
-                let res = sender.try_send(());
-                if let Err(TrySendError::Disconnected(_)) = res {
-                    println!("Channel closed. Exiting.");
-                    break;
-                }
-            }
-        });
-        Self { recv: receiver }
     }
 
-    pub async fn consume(&self) {
-        self.recv.recv_async().await.unwrap();
+    fn refill(&self, state: &mut BucketState) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.updated);
+        state.tokens = self.config.refill(state.tokens, elapsed);
+        state.updated = now;
     }
 
-    pub async fn use_rate_limit(&self, count: usize) {
-        for _ in 0..count {
-            self.consume().await;
+    pub(super) fn bucket_configuration(tier: AccountTier) -> BucketDescription {
+        let (max_size, decay_per_sec) = match tier {
+            AccountTier::Starter => (15.0, 0.33),
+            AccountTier::Intermediate => (20.0, 0.5),
+            AccountTier::Pro => (20.0, 1.0),
+            AccountTier::Custom { api, .. } => (api.max().into(), api.decay_per_sec()),
+        };
+        BucketDescription {
+            max_size,
+            decay_per_sec,
         }
     }
+}
 
-    fn bucket_configuration(tier: AccountTier) -> BucketDescription {
-        let configurations = HashMap::from([
-            (
-                AccountTier::Starter,
-                BucketDescription {
-                    max_size: 15,
-                    fill_rate: Duration::from_secs(3),
-                },
-            ),
-            (
-                AccountTier::Intermediate,
-                BucketDescription {
-                    max_size: 20,
-                    fill_rate: Duration::from_secs(2),
-                },
-            ),
-            (
-                AccountTier::Pro,
-                BucketDescription {
-                    max_size: 20,
-                    fill_rate: Duration::from_secs(1),
-                },
-            ),
-        ]);
-        let bucket = configurations.get(&tier).unwrap();
-        *bucket
+#[async_trait]
+impl RateLimiter for LeakyBucket {
+    async fn use_rate_limit(&self, count: usize) {
+        let count = self.config.clamp(count);
+        let _turn = self.queue.lock().await;
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                self.refill(&mut state);
+                if state.tokens >= count {
+                    state.tokens -= count;
+                    return;
+                }
+                self.config.wait(state.tokens, count)
+            };
+            trace::debug!(
+                tokens = count,
+                wait_ms = wait.as_millis() as u64,
+                "waiting for rate limit tokens"
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    async fn remaining(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state);
+        state.tokens as usize
     }
 }
 
 #[derive(Clone, Copy)]
-struct BucketDescription {
-    max_size: u8,
-    /// fill_rate is the amount of time that must
-    /// pass for a single element to be added back into
-    /// the bucket.
-    fill_rate: Duration,
+pub(super) struct BucketDescription {
+    pub max_size: f64,
+    /// decay_per_sec is the number of tokens
+    /// added back into the bucket every second.
+    pub decay_per_sec: f64,
+}
+
+impl BucketDescription {
+    /// Returns the tokens in the bucket after `elapsed` has passed.
+    pub fn refill(&self, tokens: f64, elapsed: Duration) -> f64 {
+        let refilled = elapsed.as_secs_f64() * self.decay_per_sec;
+        (tokens + refilled).min(self.max_size)
+    }
+
+    /// Returns how long it takes until the bucket holds `count` tokens.
+    pub fn wait(&self, tokens: f64, count: f64) -> Duration {
+        let secs = (count - tokens).max(0.0) / self.decay_per_sec;
+        // A bucket which never refills makes the caller wait forever.
+        Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+    }
+
+    /// Limits a request to the size of the bucket, since
+    /// it could never get more tokens than the bucket holds.
+    pub fn clamp(&self, count: usize) -> f64 {
+        (count as f64).min(self.max_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LeakyBucket;
+    use crate::kraken::ratelimiter::RateLimiter;
+    use crate::kraken::{AccountTier, CounterLimit};
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    #[test]
+    fn builds_outside_a_runtime() {
+        let bucket = LeakyBucket::new(AccountTier::Starter);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert_eq!(runtime.block_on(bucket.remaining()), 15);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_the_bucket_to_refill() {
+        let bucket = LeakyBucket::new(AccountTier::Intermediate);
+        bucket.use_rate_limit(20).await;
+        assert_eq!(bucket.remaining().await, 0);
+        // The Intermediate tier refills one token every 2 seconds.
+        let start = Instant::now();
+        bucket.use_rate_limit(2).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(4));
+        tokio::time::advance(Duration::from_secs(20)).await;
+        assert_eq!(bucket.remaining().await, 10);
+        // The bucket never holds more than its size.
+        tokio::time::advance(Duration::from_secs(3600)).await;
+        assert_eq!(bucket.remaining().await, 20);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn supports_custom_fractional_decay() {
+        let tier = AccountTier::Custom {
+            api: CounterLimit::new(40, 0.25).unwrap(),
+            trading: CounterLimit::new(60, 1.0).unwrap(),
+        };
+        let bucket = LeakyBucket::new(tier);
+        assert_eq!(bucket.remaining().await, 40);
+        bucket.use_rate_limit(40).await;
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(bucket.remaining().await, 2);
+        // The Starter tier decays by 0.33 per second.
+        let bucket = LeakyBucket::new(AccountTier::Starter);
+        bucket.use_rate_limit(15).await;
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(bucket.remaining().await, 3);
+    }
 }
@@ -1,3 +1,11 @@
+pub use cost::endpoint_cost;
+pub use file_rate_limiter::FileRateLimiter;
 pub use leaky_bucket::LeakyBucket;
+pub use rate_limiter::RateLimiter;
+pub use trading_counter::TradingCounter;
 
+mod cost;
+mod file_rate_limiter;
 mod leaky_bucket;
+mod rate_limiter;
+mod trading_counter;
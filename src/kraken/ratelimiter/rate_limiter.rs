@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// RateLimiter paces the requests a Client makes so they stay within
+/// Kraken's REST call counter. Every Client has its own LeakyBucket by
+/// default. Clients sharing an API key within one process can share one
+/// limiter through an `Arc`, and clients in separate processes on one host
+/// can coordinate through a FileRateLimiter.
+#[async_trait]
+pub trait RateLimiter: Send + Sync {
+    /// Waits until `count` points are available, then uses them.
+    async fn use_rate_limit(&self, count: usize);
+
+    /// Returns how many points can be used without waiting.
+    async fn remaining(&self) -> usize;
+}
+
+#[async_trait]
+impl<R: RateLimiter + ?Sized> RateLimiter for Arc<R> {
+    async fn use_rate_limit(&self, count: usize) {
+        (**self).use_rate_limit(count).await
+    }
+
+    async fn remaining(&self) -> usize {
+        (**self).remaining().await
+    }
+}
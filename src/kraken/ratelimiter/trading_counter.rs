@@ -0,0 +1,199 @@
+use crate::kraken::trace;
+use crate::kraken::AccountTier;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// Cancelling an order adds a penalty to the counter which is larger the
+// younger the order is. Orders older than the last threshold are free.
+const CANCEL_PENALTIES: [(Duration, f64); 6] = [
+    (Duration::from_secs(5), 8.0),
+    (Duration::from_secs(10), 6.0),
+    (Duration::from_secs(15), 5.0),
+    (Duration::from_secs(45), 4.0),
+    (Duration::from_secs(90), 2.0),
+    (Duration::from_secs(300), 1.0),
+];
+
+/// TradingCounter models the matching engine's per-pair rate counter.
+/// Placing an order adds 1 to the pair's counter, cancelling one adds a
+/// penalty based on how long ago it was placed, and every counter decays
+/// at a rate set by the account tier. Kraken rejects new orders on a pair
+/// while its counter is above the tier's maximum.
+pub struct TradingCounter {
+    max: f64,
+    decay_per_sec: f64,
+    state: Mutex<TradingState>,
+}
+
+#[derive(Default)]
+struct TradingState {
+    pairs: HashMap<String, PairCounter>,
+    // When and on which pair each order still worth a penalty was placed.
+    orders: HashMap<String, (String, Instant)>,
+}
+
+struct PairCounter {
+    count: f64,
+    updated: Instant,
+}
+
+impl TradingCounter {
+    pub fn new(tier: AccountTier) -> Self {
+        let (max, decay_per_sec) = match tier {
+            AccountTier::Starter => (60.0, 1.0),
+            AccountTier::Intermediate => (125.0, 2.34),
+            AccountTier::Pro => (180.0, 3.75),
+            AccountTier::Custom { trading, .. } => (trading.max().into(), trading.decay_per_sec()),
+        };
+        Self {
+            max,
+            decay_per_sec,
+            state: Mutex::new(TradingState::default()),
+        }
+    }
+
+    /// Returns how far the pair's counter is below the maximum.
+    pub fn remaining(&self, pair: &str) -> f64 {
+        let mut state = self.state.lock().unwrap();
+        let count = self.decayed(&mut state, pair);
+        (self.max - count).max(0.0)
+    }
+
+    /// Waits until an order on the pair would stay within the
+    /// maximum, then counts it against the pair.
+    pub async fn place_order(&self, pair: &str) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let count = self.decayed(&mut state, pair);
+                if count + 1.0 <= self.max {
+                    self.add(&mut state, pair, 1.0);
+                    return;
+                }
+                (count + 1.0 - self.max) / self.decay_per_sec
+            };
+            let wait = Duration::from_secs_f64(wait);
+            trace::debug!(
+                pair,
+                wait_ms = wait.as_millis() as u64,
+                "waiting for the trading counter"
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Records when the orders were placed, so that
+    /// cancelling them later can be penalized by their age.
+    pub fn orders_placed(&self, pair: &str, txids: &[String]) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let oldest = CANCEL_PENALTIES[CANCEL_PENALTIES.len() - 1].0;
+        state
+            .orders
+            .retain(|_, (_, placed)| now.duration_since(*placed) < oldest);
+        for txid in txids {
+            state.orders.insert(txid.clone(), (pair.to_owned(), now));
+        }
+    }
+
+    /// Adds the cancellation penalty for the order to its pair's counter.
+    /// Orders this counter didn't see placed, such as those placed by
+    /// another client, are assumed to be old enough to be free.
+    pub fn order_cancelled(&self, txid: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some((pair, placed)) = state.orders.remove(txid) {
+            let penalty = cancel_penalty(placed.elapsed());
+            self.add(&mut state, &pair, penalty);
+        }
+    }
+
+    // Returns the pair's counter after applying the decay since it was last updated.
+    fn decayed(&self, state: &mut TradingState, pair: &str) -> f64 {
+        let now = Instant::now();
+        match state.pairs.get_mut(pair) {
+            Some(counter) => {
+                let elapsed = now.duration_since(counter.updated).as_secs_f64();
+                counter.count = (counter.count - elapsed * self.decay_per_sec).max(0.0);
+                counter.updated = now;
+                counter.count
+            }
+            None => 0.0,
+        }
+    }
+
+    fn add(&self, state: &mut TradingState, pair: &str, amount: f64) {
+        let count = self.decayed(state, pair);
+        state.pairs.insert(
+            pair.to_owned(),
+            PairCounter {
+                count: count + amount,
+                updated: Instant::now(),
+            },
+        );
+    }
+}
+
+fn cancel_penalty(age: Duration) -> f64 {
+    CANCEL_PENALTIES
+        .iter()
+        .find(|(threshold, _)| age < *threshold)
+        .map_or(0.0, |(_, penalty)| *penalty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cancel_penalty, TradingCounter};
+    use crate::kraken::{AccountTier, CounterLimit};
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[test]
+    fn penalizes_young_orders_more() {
+        assert_eq!(cancel_penalty(Duration::from_secs(1)), 8.0);
+        assert_eq!(cancel_penalty(Duration::from_secs(12)), 5.0);
+        assert_eq!(cancel_penalty(Duration::from_secs(60)), 2.0);
+        assert_eq!(cancel_penalty(Duration::from_secs(299)), 1.0);
+        assert_eq!(cancel_penalty(Duration::from_secs(301)), 0.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn counts_orders_and_cancellations_per_pair() {
+        let counter = TradingCounter::new(AccountTier::Starter);
+        counter.place_order("XBTUSD").await;
+        counter.orders_placed("XBTUSD", &["OQCLML-BW3P3-BUCMWZ".to_owned()]);
+        counter.order_cancelled("OQCLML-BW3P3-BUCMWZ");
+        assert_eq!(counter.remaining("XBTUSD"), 51.0);
+        assert_eq!(counter.remaining("ETHUSD"), 60.0);
+        // The counter decays by 1 per second on the Starter tier.
+        tokio::time::advance(Duration::from_secs(4)).await;
+        assert_eq!(counter.remaining("XBTUSD"), 55.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_the_counter_to_decay() {
+        let counter = TradingCounter::new(AccountTier::Starter);
+        for _ in 0..60 {
+            counter.place_order("XBTUSD").await;
+        }
+        let start = tokio::time::Instant::now();
+        counter.place_order("XBTUSD").await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn follows_custom_trading_limits() {
+        let counter = TradingCounter::new(AccountTier::Custom {
+            api: CounterLimit::new(20, 1.0).unwrap(),
+            trading: CounterLimit::new(10, 0.5).unwrap(),
+        });
+        for _ in 0..10 {
+            counter.place_order("XBTUSD").await;
+        }
+        assert_eq!(counter.remaining("XBTUSD"), 0.0);
+        let start = tokio::time::Instant::now();
+        counter.place_order("XBTUSD").await;
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+}
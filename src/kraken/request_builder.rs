@@ -1,8 +1,7 @@
-use crate::kraken::signature::get_kraken_signature;
+use crate::kraken::signature::{get_futures_signature, get_kraken_signature};
 use reqwest::header::{HeaderValue, CONTENT_TYPE};
 use reqwest::{Method, Url};
 use reqwest::{Request, RequestBuilder as ReqwestBuilder};
-use serde::de::Deserialize;
 use serde::ser::Serialize;
 
 const FORM_URLENCODED: &str = "application/x-www-form-urlencoded; charset=utf-8";
@@ -34,6 +33,16 @@ pub enum PrivacyLevel {
         api_key: String,
         private_key: String,
         nonce: String,
+        // The path Kraken verifies the signature against.
+        uri_path: String,
+    },
+    // Kraken Futures authenticates with different headers
+    // and a different signature scheme.
+    FuturesPrivate {
+        api_key: String,
+        private_key: String,
+        nonce: String,
+        uri_path: String,
     },
 }
 
@@ -57,47 +66,56 @@ where
     }
 
     pub fn attach_auth_headers(&self, mut req: Request) -> Request {
-        if let PrivacyLevel::Private {
-            api_key,
-            private_key,
-            nonce,
-        } = &self.privacy_level
-        {
-            let nonce = nonce.to_string();
-            let private_key = private_key.to_string();
-            let key = HeaderValue::from_str(api_key).unwrap();
-            req.headers_mut().insert("API-Key", key);
-            let signature = get_kraken_signature(nonce, private_key, &req);
-            let api_sign = HeaderValue::from_str(&signature).unwrap();
-            req.headers_mut().insert("API-Sign", api_sign);
+        match &self.privacy_level {
+            PrivacyLevel::Public => (),
+            PrivacyLevel::Private {
+                api_key,
+                private_key,
+                nonce,
+                uri_path,
+            } => {
+                let nonce = nonce.to_string();
+                let private_key = private_key.to_string();
+                let key = HeaderValue::from_str(api_key).unwrap();
+                req.headers_mut().insert("API-Key", key);
+                let signature = get_kraken_signature(nonce, private_key, uri_path.clone(), &req);
+                let api_sign = HeaderValue::from_str(&signature).unwrap();
+                req.headers_mut().insert("API-Sign", api_sign);
+            }
+            PrivacyLevel::FuturesPrivate {
+                api_key,
+                private_key,
+                nonce,
+                uri_path,
+            } => {
+                let key = HeaderValue::from_str(api_key).unwrap();
+                req.headers_mut().insert("APIKey", key);
+                let nonce_header = HeaderValue::from_str(nonce).unwrap();
+                req.headers_mut().insert("Nonce", nonce_header);
+                let signature = get_futures_signature(
+                    nonce.to_string(),
+                    private_key.to_string(),
+                    uri_path.clone(),
+                    &req,
+                );
+                let authent = HeaderValue::from_str(&signature).unwrap();
+                req.headers_mut().insert("Authent", authent);
+            }
         }
         req
     }
 
-    pub async fn build_and_run(
-        &self,
-        client: &reqwest::Client,
-    ) -> Result<reqwest::Response, reqwest::Error> {
+    /// Builds the request, signing it if it goes to a private endpoint.
+    pub fn build(&self, client: &reqwest::Client) -> Result<Request, reqwest::Error> {
         let mut intermediate = self.build_basic_request(client);
         intermediate = self.attach_data(intermediate);
-        let mut req = intermediate.build()?;
+        let req = intermediate.build()?;
         // If the request required authentication,
         // update the headers with the API key and the signature.
-        req = self.attach_auth_headers(req);
-        let resp = client.execute(req).await?;
-        Ok(resp)
-    }
-
-    pub async fn execute<R>(self, client: &reqwest::Client) -> Result<R, reqwest::Error>
-    where
-        R: for<'de> Deserialize<'de>,
-    {
-        let response = self.build_and_run(client).await?.json::<R>().await?;
-        Ok(response)
+        Ok(self.attach_auth_headers(req))
     }
 
-    pub async fn debug(self, client: &reqwest::Client) -> Result<String, reqwest::Error> {
-        let response = self.build_and_run(client).await?.text().await?;
-        Ok(response)
+    pub fn is_private(&self) -> bool {
+        !matches!(self.privacy_level, PrivacyLevel::Public)
     }
 }
@@ -0,0 +1,139 @@
+use crate::kraken::backoff::Backoff;
+use crate::kraken::payload::kraken_errors;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+// Kraken errors which mean the request was turned away
+// before being processed, so sending it again is safe.
+// The last is how Kraken Futures reports the rate limit.
+const RETRYABLE_ERRORS: [&str; 4] = [
+    "EGeneral:Temporary lockout",
+    "EService:Unavailable",
+    "EAPI:Rate limit exceeded",
+    "apiLimitExceeded",
+];
+
+/// RetryPolicy describes when and how often the Client sends a request
+/// again after a transient failure: a connection error or timeout, a 5xx
+/// status, or a Kraken error saying the service is unavailable or the
+/// rate limit was hit. The delay between attempts grows exponentially,
+/// is capped at `max_delay`, and is spread out by a random jitter.
+///
+/// Idempotent requests are retried. Order placement is not, since
+/// a lost response does not mean the order wasn't placed, unless
+/// `dedup_orders` is set and the order carries a userref.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// How many times a request is sent again before
+    /// the last failure is returned. Zero disables retries.
+    pub max_retries: u32,
+    /// The delay before the first retry.
+    pub initial_delay: Duration,
+    /// The upper bound on the delay between retries.
+    pub max_delay: Duration,
+    /// The factor the delay grows by after each failed retry.
+    pub multiplier: f64,
+    /// The fraction of the delay, between 0 and 1, that is randomized.
+    pub jitter: f64,
+    /// Retries AddOrder calls carrying a userref, after checking the
+    /// open and closed orders for that userref to make sure the order
+    /// wasn't placed. The userref must be unique to the order.
+    pub dedup_orders: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.25,
+            dedup_orders: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns a policy which never retries.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the delay to wait before the given retry,
+    /// counting from 1, including jitter.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let backoff = Backoff {
+            initial_delay: self.initial_delay,
+            max_delay: self.max_delay,
+            multiplier: self.multiplier,
+            jitter: self.jitter,
+        };
+        backoff.delay(retry)
+    }
+
+    /// Returns true if another retry is allowed after `retries` retries.
+    pub fn should_retry(&self, retries: u32) -> bool {
+        retries < self.max_retries
+    }
+}
+
+/// Returns true if the request failed before a response arrived
+/// in a way that may succeed on another attempt.
+pub(crate) fn is_retryable_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+/// Returns true if the response reports a transient failure.
+pub(crate) fn is_retryable_response(status: StatusCode, body: &[u8]) -> bool {
+    if status.is_server_error() {
+        return true;
+    }
+    kraken_errors(body)
+        .iter()
+        .any(|err| RETRYABLE_ERRORS.iter().any(|code| err.starts_with(code)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_retryable_response, RetryPolicy};
+    use pretty_assertions::assert_eq;
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    #[test]
+    fn grows_exponentially_up_to_the_cap() {
+        let policy = RetryPolicy {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+        assert_eq!(policy.delay(4), Duration::from_secs(5));
+        assert!(policy.should_retry(2));
+        assert!(!policy.should_retry(3));
+        assert!(!RetryPolicy::none().should_retry(0));
+    }
+
+    #[test]
+    fn recognizes_transient_responses() {
+        let ok = StatusCode::OK;
+        let body = br#"{"error":["EAPI:Rate limit exceeded"]}"#;
+        assert!(is_retryable_response(ok, body));
+        let body = br#"{"error":["EService:Unavailable"]}"#;
+        assert!(is_retryable_response(ok, body));
+        let body = br#"{"error":["EOrder:Insufficient funds"]}"#;
+        assert!(!is_retryable_response(ok, body));
+        let body = br#"{"error":[],"result":{"unixtime":1}}"#;
+        assert!(!is_retryable_response(ok, body));
+        let body = br#"{"result":"error","error":"apiLimitExceeded"}"#;
+        assert!(is_retryable_response(ok, body));
+        assert!(is_retryable_response(StatusCode::BAD_GATEWAY, b"<html>"));
+        assert!(!is_retryable_response(StatusCode::NOT_FOUND, b"<html>"));
+    }
+}
@@ -63,8 +63,64 @@ impl SignatureInput {
     }
 }
 
-pub fn get_kraken_signature(nonce: String, private_key: String, req: &Request) -> String {
-    let path = req.url().path();
+pub struct FuturesSignatureInput {
+    pub private_key: String,
+    pub nonce: String,
+    pub post_data: String,
+    pub endpoint_path: String,
+}
+
+impl FuturesSignatureInput {
+    // Kraken Futures uses its own formula for the Authent header, described at
+    // https://docs.futures.kraken.com/#http-api-http-api-introduction-authentication
+    // This yields the following algorithm:
+    // • Concat the POST data, the nonce and the endpoint path.
+    // • Take the SHA256 of the concatenated value.
+    // • Base64 decode the private key: call this the "secret".
+    // • HMAC (SHA512) the SHA using the secret.
+    // • Base64 encode the HMAC signature.
+    pub fn sign(self) -> String {
+        // • Concat the POST data, the nonce and the endpoint path.
+        let concat = self.post_data + &self.nonce + &self.endpoint_path;
+        // • Take the SHA256 of the concatenated value.
+        let mut context = Context::new(&SHA256);
+        context.update(concat.as_bytes());
+        let digest = context.finish();
+        // • Create the signing key.
+        let key = SignatureInput::build_hmac_key(self.private_key);
+        // • HMAC the SHA and Base64 encode the result.
+        let tag = hmac::sign(&key, digest.as_ref());
+        BASE64.encode(tag.as_ref())
+    }
+}
+
+pub struct FuturesChallengeInput {
+    pub private_key: String,
+    pub challenge: String,
+}
+
+impl FuturesChallengeInput {
+    // Private Kraken Futures websocket feeds are authenticated by
+    // signing a challenge issued by the server:
+    // • Take the SHA256 of the challenge.
+    // • HMAC (SHA512) the SHA using the Base64 decoded private key.
+    // • Base64 encode the HMAC signature.
+    pub fn sign(self) -> String {
+        let mut context = Context::new(&SHA256);
+        context.update(self.challenge.as_bytes());
+        let digest = context.finish();
+        let key = SignatureInput::build_hmac_key(self.private_key);
+        let tag = hmac::sign(&key, digest.as_ref());
+        BASE64.encode(tag.as_ref())
+    }
+}
+
+pub fn get_kraken_signature(
+    nonce: String,
+    private_key: String,
+    uri_path: String,
+    req: &Request,
+) -> String {
     let req_body = req.body().unwrap().as_bytes().unwrap().to_vec();
     let body_str = String::from_utf8(req_body).unwrap();
     // Here, we need to calculat the API-Sign
@@ -72,7 +128,27 @@ pub fn get_kraken_signature(nonce: String, private_key: String, req: &Request) -
         private_key,
         nonce,
         encoded_payload: body_str,
-        uri_path: path.to_owned(),
+        uri_path,
+    };
+    signature.sign()
+}
+
+// Kraken Futures signs the query string in place of the body for GET requests.
+pub fn get_futures_signature(
+    nonce: String,
+    private_key: String,
+    endpoint_path: String,
+    req: &Request,
+) -> String {
+    let post_data = match req.body().and_then(|body| body.as_bytes()) {
+        Some(bytes) => String::from_utf8(bytes.to_vec()).unwrap(),
+        None => req.url().query().unwrap_or_default().to_owned(),
+    };
+    let signature = FuturesSignatureInput {
+        private_key,
+        nonce,
+        post_data,
+        endpoint_path,
     };
     signature.sign()
 }
@@ -80,7 +156,7 @@ pub fn get_kraken_signature(nonce: String, private_key: String, req: &Request) -
 #[cfg(test)]
 mod test {
 
-    use super::SignatureInput;
+    use super::{FuturesChallengeInput, FuturesSignatureInput, SignatureInput};
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -98,4 +174,62 @@ mod test {
         let observed = sig.sign();
         assert_eq!(expected, observed);
     }
+
+    // There is no worked example in the Kraken Futures docs, so these
+    // vectors were computed independently with Python's hashlib and hmac
+    // modules following the documented formula.
+    const FUTURES_TEST_KEY: &str =
+        "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==";
+
+    #[test]
+    fn test_futures_signature() {
+        let sig = FuturesSignatureInput {
+            private_key: FUTURES_TEST_KEY.to_owned(),
+            nonce: "1415957147987".to_owned(),
+            post_data: "orderType=lmt&symbol=PI_XBTUSD&side=buy&size=1&limitPrice=9400".to_owned(),
+            endpoint_path: "/api/v3/sendorder".to_owned(),
+        };
+        let expected = "QLdvdVw+LjPJLxvAb8bCLTeT/q9Lt95nEV+7CPpPXu55ucPfv3Mcx9qI8kufJvgXstUopqTUUi0F4y26Mfj1uA==".to_owned();
+        assert_eq!(expected, sig.sign());
+    }
+
+    #[test]
+    fn test_futures_signature_without_nonce_or_data() {
+        let sig = FuturesSignatureInput {
+            private_key: FUTURES_TEST_KEY.to_owned(),
+            nonce: String::new(),
+            post_data: String::new(),
+            endpoint_path: "/api/v3/accounts".to_owned(),
+        };
+        let expected = "nJH9pTKkNbq08nxmP9E1eRLJOXsXZEQWSB4zXBICD91l16ZzyGddRkBKrB55ZwPEwFQy+iSRX9DQMNlD5OmKEA==".to_owned();
+        assert_eq!(expected, sig.sign());
+    }
+
+    #[test]
+    fn test_futures_signature_from_query_string() {
+        let client = reqwest::Client::new();
+        let req = client
+            .get("https://futures.kraken.com/derivatives/api/v3/fills")
+            .query(&[("lastFillTime", "2020-07-21T12:41:52.790Z")])
+            .build()
+            .unwrap();
+        let observed = super::get_futures_signature(
+            "1616492376594".to_owned(),
+            FUTURES_TEST_KEY.to_owned(),
+            "/api/v3/fills".to_owned(),
+            &req,
+        );
+        let expected = "zdRIzZopwnI3QUP3EWlwEncGMEYTp0N3V1vOForClcSlTVGLtXjISlWCaGS6otNEFeEeVxOvw7EoFqSUJG2YeA==".to_owned();
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn test_futures_challenge() {
+        let sig = FuturesChallengeInput {
+            private_key: FUTURES_TEST_KEY.to_owned(),
+            challenge: "c100b894-1729-464d-ace1-52dbce11db42".to_owned(),
+        };
+        let expected = "I60DEDEo0uSpvAZDEDzQEN2E7nxXdIfsjIjlfxoZrgKETP11L657QPkKIKrvjmBP2g+YjKzij3wUaZuAwNaG1A==".to_owned();
+        assert_eq!(expected, sig.sign());
+    }
 }
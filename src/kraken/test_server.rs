@@ -0,0 +1,99 @@
+// A local HTTP server for tests. It answers one request per connection
+// with the next canned response, and reports every request it receives.
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use url::Url;
+
+/// Received is a request as the server saw it.
+#[derive(Debug)]
+pub(crate) struct Received {
+    pub method: String,
+    /// The path and query, such as "/0/public/Ticker?pair=XBTUSD".
+    pub target: String,
+    /// The headers, keyed by their lowercased names.
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl Received {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(String::as_str)
+    }
+}
+
+/// Serves each (status, body) in turn, holding every response back for
+/// `delay`, then stops accepting connections.
+pub(crate) async fn serve(
+    delay: Duration,
+    responses: Vec<(u16, &'static str)>,
+) -> (Url, UnboundedReceiver<Received>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    let (received, requests) = unbounded_channel();
+    tokio::spawn(async move {
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(respond(stream, received.clone(), delay, status, body));
+        }
+    });
+    (url, requests)
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    received: UnboundedSender<Received>,
+    delay: Duration,
+    status: u16,
+    body: &'static str,
+) {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 4096];
+    let request = loop {
+        let n = stream.read(&mut chunk).await.unwrap();
+        if n == 0 {
+            return;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(request) = parse(&buf) {
+            break request;
+        }
+    };
+    let _ = received.send(request);
+    tokio::time::sleep(delay).await;
+    let response = format!(
+        "HTTP/1.1 {} Canned\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+// Parses the request once all of it has arrived.
+fn parse(buf: &[u8]) -> Option<Received> {
+    let text = String::from_utf8_lossy(buf);
+    let (head, body) = text.split_once("\r\n\r\n")?;
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_owned();
+    let target = request_line.next()?.to_owned();
+    let headers: HashMap<_, _> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.to_lowercase(), value.trim().to_owned()))
+        .collect();
+    let length = headers
+        .get("content-length")
+        .map_or(0, |len| len.parse().unwrap());
+    if body.len() < length {
+        return None;
+    }
+    Some(Received {
+        method,
+        target,
+        headers,
+        body: body.to_owned(),
+    })
+}
@@ -0,0 +1,241 @@
+use super::{MockResponse, RecordedRequest};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Parameters which change on every request or grant access to the account.
+const SCRUBBED_PARAMS: [&str; 2] = ["nonce", "otp"];
+
+// Response fields which grant access to the account, such as the token
+// GetWebSocketsToken returns. Their values are replaced wherever they
+// appear in a body, so the response still decodes when replayed.
+const SCRUBBED_FIELDS: [&str; 1] = ["token"];
+
+const SCRUBBED: &str = "<scrubbed>";
+
+/// Cassette holds requests and the responses Kraken gave them, recorded
+/// by MockKraken::record and served again by MockKraken::replay. It is
+/// stored as JSON, with the request parameters in a sorted object and the
+/// response body as JSON where it parses as such, or as a `raw` string
+/// where it doesn't:
+///
+/// ```json
+/// {"interactions": [{
+///     "request": {"method": "POST", "path": "/private/Balance", "params": {}},
+///     "response": {"status": 200, "body": {"error": [], "result": {"ZUSD": "100.0"}}}
+/// }]}
+/// ```
+///
+/// Headers aren't kept, and neither are the nonce and one-time password,
+/// so a cassette stays the same between runs. Response fields known to
+/// grant access, such as a websocket token, are replaced with
+/// "<scrubbed>". Anything else in a response is kept as Kraken sent it,
+/// balances and order details included, so review a cassette recorded
+/// against a real account before committing it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Interaction {
+    pub request: CassetteRequest,
+    pub response: CassetteResponse,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CassetteRequest {
+    pub method: String,
+    /// The endpoint, without the API version, such as "/public/Ticker".
+    pub path: String,
+    /// The query or form parameters, without the nonce and one-time password.
+    pub params: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CassetteResponse {
+    pub status: u16,
+    /// The body, if it is JSON.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub body: Value,
+    /// The body as Kraken sent it, if it isn't JSON,
+    /// such as an HTML error page from the load balancer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+}
+
+impl Cassette {
+    /// Reads a cassette saved with Cassette::save.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read(path)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    /// Writes the cassette as pretty-printed JSON, ready to commit as a fixture.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Removes and returns the response to the first
+    /// interaction whose request matches `req`.
+    pub(crate) fn take(&mut self, req: &RecordedRequest) -> Option<MockResponse> {
+        let req = CassetteRequest::new(req);
+        let index = self.interactions.iter().position(|i| i.request == req)?;
+        Some(self.interactions.remove(index).response.into())
+    }
+}
+
+impl CassetteRequest {
+    pub(crate) fn new(req: &RecordedRequest) -> Self {
+        let mut params: BTreeMap<_, _> = req.params().into_iter().collect();
+        for param in SCRUBBED_PARAMS {
+            params.remove(param);
+        }
+        Self {
+            method: req.method.to_string(),
+            path: req.path.clone(),
+            params,
+        }
+    }
+}
+
+impl CassetteResponse {
+    pub(crate) fn new(status: StatusCode, body: &[u8]) -> Self {
+        let (body, raw) = match serde_json::from_slice(body) {
+            Ok(mut body) => {
+                scrub(&mut body);
+                (body, None)
+            }
+            Err(_) => (
+                Value::Null,
+                Some(String::from_utf8_lossy(body).into_owned()),
+            ),
+        };
+        Self {
+            status: status.as_u16(),
+            body,
+            raw,
+        }
+    }
+}
+
+fn scrub(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                match SCRUBBED_FIELDS.contains(&name.as_str()) {
+                    true => *value = Value::String(SCRUBBED.to_owned()),
+                    false => scrub(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub),
+        _ => {}
+    }
+}
+
+impl From<CassetteResponse> for MockResponse {
+    fn from(resp: CassetteResponse) -> Self {
+        let status = StatusCode::from_u16(resp.status).unwrap_or(StatusCode::OK);
+        match resp.raw {
+            Some(raw) => MockResponse::raw(status, raw),
+            None => MockResponse::raw(status, resp.body.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cassette, CassetteResponse};
+    use crate::kraken::testing::{MockKraken, MockResponse};
+    use crate::kraken::{AccountTier, Client, ACCOUNT_BALANCE, SYSTEM_TIME};
+    use pretty_assertions::assert_eq;
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn scrubs_tokens_from_responses() {
+        let body = json!({
+            "error": [],
+            "result": {"token": "1Dwc4lzSwNWOAwkMdqhssNNFhs1ed606d1WcF3XfEMw", "expires": 900}
+        });
+        let resp = CassetteResponse::new(StatusCode::OK, body.to_string().as_bytes());
+        assert_eq!(
+            resp.body,
+            json!({"error": [], "result": {"token": "<scrubbed>", "expires": 900}})
+        );
+    }
+
+    #[test]
+    fn keeps_bodies_which_arent_json_apart() {
+        let html = CassetteResponse::new(StatusCode::BAD_GATEWAY, b"<html>Bad Gateway</html>");
+        let string = CassetteResponse::new(StatusCode::OK, br#""<html>Bad Gateway</html>""#);
+        assert_eq!(
+            serde_json::to_value(&html).unwrap(),
+            json!({"status": 502, "raw": "<html>Bad Gateway</html>"})
+        );
+        assert_eq!(
+            serde_json::to_value(&string).unwrap(),
+            json!({"status": 200, "body": "<html>Bad Gateway</html>"})
+        );
+        assert_eq!(MockResponse::from(html).body, "<html>Bad Gateway</html>");
+        assert_eq!(
+            MockResponse::from(string).body,
+            r#""<html>Bad Gateway</html>""#
+        );
+    }
+
+    #[tokio::test]
+    async fn records_and_replays_without_secrets() {
+        let kraken = MockKraken::start().await.unwrap();
+        kraken.respond(ACCOUNT_BALANCE, MockResponse::ok(json!({"ZUSD": "100.0"})));
+        let recorder = MockKraken::record(kraken.url()).await.unwrap();
+        let client = Client::builder(AccountTier::Pro)
+            .base_url(recorder.url())
+            .credentials(kraken.credentials())
+            .build()
+            .unwrap();
+        assert!(client.account_balance().await.unwrap().error.is_empty());
+        client.server_time().await.unwrap();
+        drop(kraken);
+
+        let path = std::env::temp_dir().join(format!("kraken-cassette-{}", std::process::id()));
+        recorder.cassette().save(&path).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        let cassette = Cassette::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(!json.contains("nonce"));
+        assert!(!json.contains("mock-api-key"));
+        assert_eq!(cassette.interactions.len(), 2);
+        assert_eq!(cassette.interactions[0].request.path, ACCOUNT_BALANCE);
+        assert_eq!(cassette.interactions[1].request.path, SYSTEM_TIME);
+
+        let replay = MockKraken::replay(cassette).await.unwrap();
+        let client = replay.builder(AccountTier::Pro).build().unwrap();
+        let resp = client.account_balance().await.unwrap();
+        assert_eq!(resp.result.values().next().unwrap(), "100.0");
+        assert_eq!(replay.cassette().interactions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn names_requests_missing_from_the_cassette() {
+        let replay = MockKraken::replay(Cassette::default()).await.unwrap();
+        let client = replay.builder(AccountTier::Pro).build().unwrap();
+        let resp = client.server_time().await.unwrap();
+        assert_eq!(
+            resp.error,
+            vec!["EGeneral:No interaction recorded for GET /public/Time {}"]
+        );
+        let resp = client.account_balance().await.unwrap();
+        assert_eq!(
+            resp.error,
+            vec!["EGeneral:No interaction recorded for POST /private/Balance {}"]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// MockResponse is a response MockKraken sends back to a request.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub(crate) status: StatusCode,
+    pub(crate) body: String,
+    pub(crate) delay: Duration,
+}
+
+impl MockResponse {
+    /// A successful response carrying `result`, wrapped the way Kraken
+    /// wraps every result: `{"error": [], "result": result}`.
+    pub fn ok(result: Value) -> Self {
+        Self::json(json!({ "error": [], "result": result }))
+    }
+
+    /// A response with `body` as it is, with status 200.
+    pub fn json(body: Value) -> Self {
+        Self::raw(StatusCode::OK, body.to_string())
+    }
+
+    /// A response with any status and body, such as a truncated body.
+    pub fn raw(status: StatusCode, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// A response reporting Kraken error codes, such as
+    /// "EOrder:Insufficient funds". Kraken sends these with status 200.
+    pub fn errors(errors: &[&str]) -> Self {
+        Self::json(json!({ "error": errors }))
+    }
+
+    /// Kraken's rejection of a request over the rate limit.
+    pub fn rate_limited() -> Self {
+        Self::errors(&["EAPI:Rate limit exceeded"])
+    }
+
+    /// An HTTP error with an empty body, such as a 502 from the load balancer.
+    pub fn status(status: StatusCode) -> Self {
+        Self::raw(status, "")
+    }
+
+    /// Holds the response back for `delay`, to simulate latency
+    /// or to make the client time out.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
@@ -0,0 +1,513 @@
+use super::cassette::{Cassette, CassetteRequest, CassetteResponse, Interaction};
+use super::MockResponse;
+use crate::kraken::signature::SignatureInput;
+use crate::kraken::{
+    AccountTier, Authenticated, Client, ClientBuilder, KrakenCredentials, DEFAULT_API_VERSION,
+    SYSTEM_STATUS, SYSTEM_TIME,
+};
+use chrono::{SecondsFormat, Utc};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Server};
+use reqwest::header::{HeaderMap, CONTENT_LENGTH, CONTENT_TYPE, HOST};
+use reqwest::{Method, StatusCode, Url};
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::io;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+// The credentials MockKraken accepts. The secret is the example from
+// Kraken's documentation, so it is a valid base64 encoded key.
+const API_KEY: &str = "mock-api-key";
+const PRIVATE_KEY: &str =
+    "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==";
+
+type Handler = Arc<dyn Fn(&RecordedRequest) -> MockResponse + Send + Sync>;
+
+/// MockKraken serves Kraken's REST API from a local port, so code built
+/// on this crate can be tested without reaching Kraken. Point a Client at
+/// it with `base_url`, or start from MockKraken::builder.
+///
+/// Each endpoint is answered by the responses queued with `respond_once`,
+/// in order, then by the response set with `respond` or `respond_with`.
+/// Time and SystemStatus answer with the current time and "online" until
+/// told otherwise, and every other endpoint with "EGeneral:Unknown method".
+///
+/// Private requests are checked the way Kraken checks them: the API-Key
+/// must be the mock's, the API-Sign must match the body, and every nonce
+/// must be greater than the last. A request failing a check is answered
+/// with Kraken's error for it, such as "EAPI:Invalid nonce".
+///
+/// MockKraken::record and MockKraken::replay start the server in the
+/// modes which record a Cassette from Kraken and serve it back offline.
+pub struct MockKraken {
+    url: Url,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+/// RecordedRequest is a request MockKraken received.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    /// The endpoint, without the API version, such as "/public/Ticker".
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Returns the parameters sent in the query or the form-encoded body.
+    pub fn params(&self) -> HashMap<String, String> {
+        let query = self.query.as_deref().unwrap_or_default();
+        url::form_urlencoded::parse(query.as_bytes())
+            .chain(url::form_urlencoded::parse(self.body.as_bytes()))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    once: HashMap<String, VecDeque<MockResponse>>,
+    handlers: HashMap<String, Handler>,
+    requests: Vec<RecordedRequest>,
+    last_nonce: Option<u64>,
+    mode: Mode,
+    // The interactions recorded, or those left to replay.
+    cassette: Cassette,
+}
+
+#[derive(Default)]
+enum Mode {
+    // Answers with the responses set on the MockKraken.
+    #[default]
+    Mock,
+    // Forwards every request upstream and records the interactions.
+    Record {
+        upstream: Url,
+        http: reqwest::Client,
+    },
+    // Answers from the cassette where no response was set.
+    Replay,
+}
+
+impl MockKraken {
+    /// Starts the server on a free port of the loopback interface.
+    /// It runs on the current Tokio runtime and stops when dropped.
+    pub async fn start() -> io::Result<Self> {
+        Self::serve(State::default())
+    }
+
+    /// Starts a server which forwards every request to `upstream`,
+    /// such as "https://api.kraken.com", and records each interaction.
+    /// Requests are passed on as they are, so clients should use real
+    /// credentials. Collect the recording with MockKraken::cassette.
+    pub async fn record(upstream: Url) -> io::Result<Self> {
+        Self::serve(State {
+            mode: Mode::Record {
+                upstream,
+                http: reqwest::Client::new(),
+            },
+            ..State::default()
+        })
+    }
+
+    /// Starts a server which answers each request with the first unused
+    /// interaction in `cassette` with the same method, path and parameters,
+    /// ignoring the nonce. Credentials aren't checked, and responses set
+    /// with `respond_once`, `respond` or `respond_with` take precedence.
+    /// A request missing from the cassette is answered with a 404 and an
+    /// "EGeneral:No interaction recorded" error naming the request.
+    pub async fn replay(cassette: Cassette) -> io::Result<Self> {
+        Self::serve(State {
+            mode: Mode::Replay,
+            cassette,
+            ..State::default()
+        })
+    }
+
+    fn serve(state: State) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = Url::parse(&format!("http://{}", listener.local_addr()?)).unwrap();
+        let state = Arc::new(Mutex::new(state));
+        let shared = state.clone();
+        let make_svc = make_service_fn(move |_conn| {
+            let state = shared.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+        });
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let server = Server::from_tcp(listener)
+            .map_err(io::Error::other)?
+            .serve(make_svc)
+            .with_graceful_shutdown(async {
+                stopped.await.ok();
+            });
+        tokio::spawn(server);
+        Ok(Self {
+            url,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Returns the URL to pass to ClientBuilder::base_url.
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// Returns the only credentials the server accepts.
+    pub fn credentials(&self) -> KrakenCredentials {
+        KrakenCredentials::new(API_KEY.to_owned(), PRIVATE_KEY.to_owned())
+    }
+
+    /// Returns a builder for a Client which talks to the server
+    /// with its credentials, ready for further configuration.
+    pub fn builder(&self, tier: AccountTier) -> ClientBuilder<Authenticated> {
+        Client::builder(tier)
+            .base_url(self.url())
+            .credentials(self.credentials())
+    }
+
+    /// Answers every request to `path`, such as "/public/Ticker",
+    /// with `resp`, once the responses queued for it have been used.
+    pub fn respond(&self, path: &str, resp: MockResponse) {
+        self.respond_with(path, move |_| resp.clone());
+    }
+
+    /// Answers every request to `path` with the response `handler`
+    /// builds from it, once the responses queued for it have been used.
+    pub fn respond_with<F>(&self, path: &str, handler: F)
+    where
+        F: Fn(&RecordedRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let mut state = self.state.lock().unwrap();
+        state.handlers.insert(path.to_owned(), Arc::new(handler));
+    }
+
+    /// Queues `resp` to answer the next request to `path` which passes
+    /// the authentication checks. Queue several to script a sequence,
+    /// such as a rate limit rejection followed by a success.
+    pub fn respond_once(&self, path: &str, resp: MockResponse) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .once
+            .entry(path.to_owned())
+            .or_default()
+            .push_back(resp);
+    }
+
+    /// Returns every request received so far, in order,
+    /// including those which failed the authentication checks.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns the interactions recorded so far, or when
+    /// replaying, the interactions which haven't been replayed.
+    pub fn cassette(&self) -> Cassette {
+        self.state.lock().unwrap().cassette.clone()
+    }
+}
+
+impl Drop for MockKraken {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    req: hyper::Request<Body>,
+) -> Result<hyper::Response<Body>, Infallible> {
+    let (parts, body) = req.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let version = format!("/{}", DEFAULT_API_VERSION);
+    let full_path = parts.uri.path();
+    let req = RecordedRequest {
+        method: parts.method.clone(),
+        path: full_path
+            .strip_prefix(&version)
+            .unwrap_or(full_path)
+            .to_owned(),
+        query: parts.uri.query().map(str::to_owned),
+        headers: parts.headers.clone(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    let upstream = match &state.lock().unwrap().mode {
+        Mode::Record { upstream, http } => Some((upstream.clone(), http.clone())),
+        _ => None,
+    };
+    let resp = match upstream {
+        Some((upstream, http)) => record(&state, &upstream, &http, req, full_path).await,
+        None => respond(&state, req, full_path),
+    };
+    tokio::time::sleep(resp.delay).await;
+    let resp = hyper::Response::builder()
+        .status(resp.status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(resp.body))
+        .unwrap();
+    Ok(resp)
+}
+
+fn respond(state: &Mutex<State>, req: RecordedRequest, full_path: &str) -> MockResponse {
+    let handler = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(req.clone());
+        if matches!(state.mode, Mode::Mock) && req.path.starts_with("/private/") {
+            if let Err(err) = state.authenticate(&req, full_path) {
+                return MockResponse::errors(&[err]);
+            }
+        }
+        if let Some(resp) = state.once.get_mut(&req.path).and_then(VecDeque::pop_front) {
+            return resp;
+        }
+        match state.handlers.get(&req.path).cloned() {
+            Some(handler) => handler,
+            None => {
+                return match state.mode {
+                    Mode::Replay => state
+                        .cassette
+                        .take(&req)
+                        .unwrap_or_else(|| missing_interaction(&req)),
+                    _ => canned_response(&req.path),
+                };
+            }
+        }
+    };
+    // The handler runs without the lock, so it may use the MockKraken.
+    handler(&req)
+}
+
+async fn record(
+    state: &Mutex<State>,
+    upstream: &Url,
+    http: &reqwest::Client,
+    req: RecordedRequest,
+    full_path: &str,
+) -> MockResponse {
+    state.lock().unwrap().requests.push(req.clone());
+    let mut url = upstream.clone();
+    url.set_path(&format!(
+        "{}{}",
+        upstream.path().trim_end_matches('/'),
+        full_path
+    ));
+    url.set_query(req.query.as_deref());
+    let mut headers = req.headers.clone();
+    headers.remove(HOST);
+    headers.remove(CONTENT_LENGTH);
+    let sent = http
+        .request(req.method.clone(), url)
+        .headers(headers)
+        .body(req.body.clone())
+        .send();
+    let result = async {
+        let resp = sent.await?;
+        let status = resp.status();
+        Ok::<_, reqwest::Error>((status, resp.bytes().await?))
+    };
+    match result.await {
+        Ok((status, body)) => {
+            let interaction = Interaction {
+                request: CassetteRequest::new(&req),
+                response: CassetteResponse::new(status, &body),
+            };
+            state
+                .lock()
+                .unwrap()
+                .cassette
+                .interactions
+                .push(interaction);
+            MockResponse::raw(status, String::from_utf8_lossy(&body))
+        }
+        // Nothing is recorded, as Kraken never answered.
+        Err(_) => MockResponse::status(StatusCode::BAD_GATEWAY),
+    }
+}
+
+impl State {
+    // Option::is_none_or, which clippy suggests over map_or, needs Rust 1.82.
+    #[allow(clippy::unnecessary_map_or)]
+    fn authenticate(&mut self, req: &RecordedRequest, full_path: &str) -> Result<(), &'static str> {
+        let header = |name: &str| req.headers.get(name).and_then(|v| v.to_str().ok());
+        if header("API-Key") != Some(API_KEY) {
+            return Err("EAPI:Invalid key");
+        }
+        let nonce = req.params().remove("nonce").unwrap_or_default();
+        let signature = SignatureInput {
+            private_key: PRIVATE_KEY.to_owned(),
+            nonce: nonce.clone(),
+            encoded_payload: req.body.clone(),
+            uri_path: full_path.to_owned(),
+        };
+        if header("API-Sign") != Some(signature.sign().as_str()) {
+            return Err("EAPI:Invalid signature");
+        }
+        match nonce.parse::<u64>() {
+            Ok(nonce) if self.last_nonce.map_or(true, |last| nonce > last) => {
+                self.last_nonce = Some(nonce);
+                Ok(())
+            }
+            _ => Err("EAPI:Invalid nonce"),
+        }
+    }
+}
+
+// Answers a request the cassette can't, naming it, so that a test fails
+// on the missing interaction rather than on a response Kraken never gave.
+fn missing_interaction(req: &RecordedRequest) -> MockResponse {
+    let req = CassetteRequest::new(req);
+    let params = serde_json::to_string(&req.params).unwrap_or_default();
+    let error = format!(
+        "EGeneral:No interaction recorded for {} {} {}",
+        req.method, req.path, params
+    );
+    MockResponse::raw(
+        StatusCode::NOT_FOUND,
+        json!({ "error": [error] }).to_string(),
+    )
+}
+
+fn canned_response(path: &str) -> MockResponse {
+    let now = Utc::now();
+    match path {
+        SYSTEM_TIME => MockResponse::ok(json!({
+            "unixtime": now.timestamp(),
+            "rfc1123": now.format("%a, %d %b %y %H:%M:%S +0000").to_string(),
+        })),
+        SYSTEM_STATUS => MockResponse::ok(json!({
+            "status": "online",
+            "timestamp": now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        })),
+        _ => MockResponse::raw(
+            StatusCode::NOT_FOUND,
+            json!({ "error": ["EGeneral:Unknown method"] }).to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockKraken;
+    use crate::kraken::testing::MockResponse;
+    use crate::kraken::{
+        AccountTier, Client, KrakenCredentials, NonceProvider, RateLimiter, RetryPolicy,
+        ACCOUNT_BALANCE, SYSTEM_TIME,
+    };
+    use async_trait::async_trait;
+    use pretty_assertions::assert_eq;
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+
+    struct FixedNonce;
+
+    // Holds the first request back, letting later ones overtake it.
+    #[derive(Default)]
+    struct HoldFirst(AtomicBool);
+
+    #[async_trait]
+    impl RateLimiter for HoldFirst {
+        async fn use_rate_limit(&self, _count: usize) {
+            if !self.0.swap(true, Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+
+        async fn remaining(&self) -> usize {
+            usize::MAX
+        }
+    }
+
+    #[async_trait]
+    impl NonceProvider for FixedNonce {
+        async fn next_nonce(&self) -> io::Result<u64> {
+            Ok(1)
+        }
+    }
+
+    #[tokio::test]
+    async fn verifies_private_requests() {
+        let mock = MockKraken::start().await.unwrap();
+        mock.respond(ACCOUNT_BALANCE, MockResponse::ok(json!({"ZUSD": "100.0"})));
+        let client = mock.builder(AccountTier::Pro).build().unwrap();
+        let resp = client.account_balance().await.unwrap();
+        assert_eq!(resp.error, Vec::<String>::new());
+        assert_eq!(resp.result.values().next().unwrap(), "100.0");
+
+        let other = KrakenCredentials::new("mock-api-key".to_owned(), "c2VjcmV0".to_owned());
+        let forged = Client::builder(AccountTier::Pro)
+            .base_url(mock.url())
+            .credentials(other)
+            .build()
+            .unwrap();
+        let resp = forged.account_balance().await.unwrap();
+        assert_eq!(resp.error, vec!["EAPI:Invalid signature"]);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].path, ACCOUNT_BALANCE);
+        assert!(requests[0].params().contains_key("nonce"));
+    }
+
+    #[tokio::test]
+    async fn rejects_reused_nonces() {
+        let mock = MockKraken::start().await.unwrap();
+        mock.respond(ACCOUNT_BALANCE, MockResponse::ok(json!({})));
+        let client = mock
+            .builder(AccountTier::Pro)
+            .nonce_provider(FixedNonce)
+            .build()
+            .unwrap();
+        assert!(client.account_balance().await.unwrap().error.is_empty());
+        let resp = client.account_balance().await.unwrap();
+        assert_eq!(resp.error, vec!["EAPI:Invalid nonce"]);
+    }
+
+    #[tokio::test]
+    async fn signs_requests_after_waiting_on_the_rate_limiter() {
+        let mock = MockKraken::start().await.unwrap();
+        mock.respond(ACCOUNT_BALANCE, MockResponse::ok(json!({})));
+        let client = mock
+            .builder(AccountTier::Pro)
+            .rate_limiter(HoldFirst::default())
+            .build()
+            .unwrap();
+        let (held, overtaking) = tokio::join!(client.account_balance(), client.account_balance());
+        assert_eq!(held.unwrap().error, Vec::<String>::new());
+        assert_eq!(overtaking.unwrap().error, Vec::<String>::new());
+    }
+
+    #[tokio::test]
+    async fn injects_failures_and_latency() {
+        let mock = MockKraken::start().await.unwrap();
+        let latency = Duration::from_millis(50);
+        mock.respond_once(SYSTEM_TIME, MockResponse::rate_limited());
+        mock.respond_once(
+            SYSTEM_TIME,
+            MockResponse::status(StatusCode::BAD_GATEWAY).delay(latency),
+        );
+        let client = mock
+            .builder(AccountTier::Pro)
+            .retry_policy(RetryPolicy {
+                initial_delay: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            })
+            .build()
+            .unwrap();
+        let start = Instant::now();
+        let resp = client.server_time().await.unwrap();
+        assert!(start.elapsed() >= latency);
+        assert!(resp.error.is_empty());
+        assert_eq!(mock.requests().len(), 3);
+    }
+}
@@ -0,0 +1,7 @@
+pub use cassette::{Cassette, CassetteRequest, CassetteResponse, Interaction};
+pub use mock_response::MockResponse;
+pub use mock_server::{MockKraken, RecordedRequest};
+
+mod cassette;
+mod mock_response;
+mod mock_server;
@@ -0,0 +1,26 @@
+// Forward to the tracing crate when the "tracing" feature is enabled, and
+// expand to nothing otherwise, so call sites don't need cfg attributes.
+// Never pass headers, credentials or request bodies to these macros:
+// private requests carry the API key and signature in their headers.
+
+#[cfg(feature = "tracing")]
+macro_rules! debug {
+    ($($arg:tt)*) => { ::tracing::debug!($($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {};
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! warning {
+    ($($arg:tt)*) => { ::tracing::warn!($($arg)*) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! warning {
+    ($($arg:tt)*) => {};
+}
+
+pub(crate) use {debug, warning};
@@ -0,0 +1,158 @@
+use crate::kraken::client_error::ClientError;
+use crate::kraken::middleware::{Middleware, RequestInfo, ResponseInfo};
+use crate::kraken::nonce::NonceProvider;
+use crate::kraken::ratelimiter::RateLimiter;
+use crate::kraken::request_builder::RequestBuilder;
+use crate::kraken::retry_policy::{is_retryable_error, is_retryable_response, RetryPolicy};
+use crate::kraken::trace;
+use reqwest::{Request, StatusCode};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::time::Instant;
+
+/// Transport sends the requests of both the spot and the futures client,
+/// built from the options on a ClientBuilder. It waits on the rate
+/// limiter, signs each attempt with a fresh nonce, runs the middleware,
+/// and retries transient failures according to the RetryPolicy.
+pub(crate) struct Transport {
+    pub http: reqwest::Client,
+    pub rate_limiter: Arc<dyn RateLimiter>,
+    pub nonces: Arc<dyn NonceProvider>,
+    pub retry: RetryPolicy,
+    pub middleware: Vec<Arc<dyn Middleware>>,
+}
+
+/// Whether a request may be sent again after a transient failure.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Retry {
+    Idempotent,
+    Never,
+}
+
+/// The outcome of sending a request once.
+pub(crate) struct Attempt {
+    pub body: Result<Vec<u8>, reqwest::Error>,
+    pub retryable: bool,
+}
+
+impl Attempt {
+    fn failed(err: reqwest::Error) -> Self {
+        Self {
+            retryable: is_retryable_error(&err),
+            body: Err(err),
+        }
+    }
+}
+
+impl Transport {
+    pub async fn use_rate_limit(&self, count: usize) {
+        self.rate_limiter.use_rate_limit(count).await
+    }
+
+    pub async fn nonce(&self) -> Result<String, ClientError> {
+        let nonce = self.nonces.next_nonce().await.map_err(ClientError::Nonce)?;
+        Ok(nonce.to_string())
+    }
+
+    // Sends one attempt. The caller must have waited on the rate limiter
+    // already: private requests are signed with a nonce only after the
+    // wait, so a request which waited can't be overtaken by a later one
+    // carrying a higher nonce.
+    pub async fn attempt<F: Serialize>(&self, req: RequestBuilder<F>) -> Attempt {
+        let request = match req.build(&self.http) {
+            Ok(request) => request,
+            Err(err) => return Attempt::failed(err),
+        };
+        let info = match self.middleware.is_empty() {
+            true => None,
+            false => Some(RequestInfo::new(&request, req.is_private())),
+        };
+        if let Some(info) = &info {
+            self.middleware.iter().for_each(|m| m.before_request(info));
+        }
+        let start = Instant::now();
+        let result = self.execute(request).await;
+        let latency = start.elapsed();
+        if info.is_some() || cfg!(feature = "tracing") {
+            let resp = ResponseInfo::new(&result, latency);
+            trace::debug!(
+                status = ?resp.status,
+                latency_ms = latency.as_millis() as u64,
+                errors = ?resp.errors,
+                "received response"
+            );
+            if let Some(info) = &info {
+                self.middleware
+                    .iter()
+                    .for_each(|m| m.after_response(info, &resp));
+            }
+        }
+        match result {
+            Ok((status, body)) => Attempt {
+                retryable: is_retryable_response(status, &body),
+                body: Ok(body),
+            },
+            Err(err) => {
+                trace::warning!(error = %err, "request failed");
+                Attempt::failed(err)
+            }
+        }
+    }
+
+    // Sends the request and reads the whole body, leaving
+    // the caller to decide whether it is worth decoding.
+    async fn execute(&self, req: Request) -> Result<(StatusCode, Vec<u8>), reqwest::Error> {
+        let resp = self.http.execute(req).await?;
+        let status = resp.status();
+        let body = resp.bytes().await?;
+        Ok((status, body.to_vec()))
+    }
+
+    // Sends the request `build` makes for the endpoint at `path`, charging
+    // `cost` to the rate limiter for every attempt and retrying transient
+    // failures according to the RetryPolicy. The request is built again
+    // for every attempt, and when `signed`, each attempt gets a fresh
+    // nonce once the rate limiter lets it through, which is passed to `build`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", name = "kraken", skip_all, fields(endpoint = path))
+    )]
+    pub async fn send<F, B>(
+        &self,
+        path: &str,
+        cost: usize,
+        retry: Retry,
+        signed: bool,
+        build: B,
+    ) -> Result<Vec<u8>, ClientError>
+    where
+        F: Serialize,
+        B: Fn(&str, Option<String>) -> Result<RequestBuilder<F>, ClientError>,
+    {
+        let mut retries = 0;
+        loop {
+            self.use_rate_limit(cost).await;
+            let nonce = match signed {
+                true => Some(self.nonce().await?),
+                false => None,
+            };
+            let attempt = self.attempt(build(path, nonce)?).await;
+            let may_retry = retry == Retry::Idempotent && self.retry.should_retry(retries);
+            if !(attempt.retryable && may_retry) {
+                return Ok(attempt.body?);
+            }
+            retries += 1;
+            self.wait_to_retry(retries).await;
+        }
+    }
+
+    pub async fn wait_to_retry(&self, retry: u32) {
+        let delay = self.retry.delay(retry);
+        trace::debug!(
+            retry,
+            delay_ms = delay.as_millis() as u64,
+            "retrying after a transient failure"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
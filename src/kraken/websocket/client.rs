@@ -0,0 +1,262 @@
+use super::connection::{self, Command, Handled, Protocol};
+use super::{ReconnectPolicy, SequenceTracker, Subscription, TokenProvider, WebSocketEvent};
+use crate::kraken::{WEBSOCKET_PRIVATE, WEBSOCKET_PUBLIC};
+use async_trait::async_trait;
+use flume::{Receiver, Sender};
+use serde_json::Value;
+use url::Url;
+
+/// WebSocketClient maintains a connection to one of Kraken's websocket
+/// endpoints in a background task. When the connection drops it
+/// reconnects with exponential backoff and resubscribes to every
+/// active channel. Dropping the client closes the connection.
+/// The constructors spawn the task, so they must be awaited within
+/// a Tokio runtime, though the client may be created outside one.
+pub struct WebSocketClient {
+    commands: Sender<Command<Subscription>>,
+    events: Receiver<WebSocketEvent>,
+}
+
+impl WebSocketClient {
+    /// Connects to the public endpoint at `wss://ws.kraken.com`.
+    pub async fn public(policy: ReconnectPolicy) -> Self {
+        Self::connect(Url::parse(WEBSOCKET_PUBLIC).unwrap(), policy).await
+    }
+
+    /// Connects to the authenticated endpoint at `wss://ws-auth.kraken.com`.
+    /// A token is fetched from `tokens` before every connection, and
+    /// replaces the token of each subscription which carries one.
+    pub async fn private<T: TokenProvider + 'static>(policy: ReconnectPolicy, tokens: T) -> Self {
+        Self::connect_private(Url::parse(WEBSOCKET_PRIVATE).unwrap(), policy, tokens).await
+    }
+
+    /// Connects to `url`, such as a local test server. Subscriptions
+    /// are replayed with the tokens they were made with.
+    pub async fn connect(url: Url, policy: ReconnectPolicy) -> Self {
+        Self::spawn(url, policy, None)
+    }
+
+    /// Connects to `url`, fetching a token from `tokens` before every
+    /// connection as WebSocketClient::private does.
+    pub async fn connect_private<T: TokenProvider + 'static>(
+        url: Url,
+        policy: ReconnectPolicy,
+        tokens: T,
+    ) -> Self {
+        Self::spawn(url, policy, Some(Box::new(tokens)))
+    }
+
+    fn spawn(url: Url, policy: ReconnectPolicy, tokens: Option<Box<dyn TokenProvider>>) -> Self {
+        let protocol = SpotProtocol {
+            sequences: SequenceTracker::new(),
+            tokens,
+            token: None,
+        };
+        let (commands, events) = connection::spawn(url, policy, protocol);
+        Self { commands, events }
+    }
+
+    /// Subscribes to a channel. The subscription is replayed
+    /// every time the client reconnects until it is unsubscribed.
+    pub fn subscribe(&self, subscription: Subscription) {
+        // The worker only stops once this client is dropped
+        // or has given up, in which case there's nothing to do.
+        let _ = self.commands.send(Command::Subscribe(subscription));
+    }
+
+    pub fn unsubscribe(&self, subscription: Subscription) {
+        let _ = self.commands.send(Command::Unsubscribe(subscription));
+    }
+
+    /// Waits for the next event. Returns None once the
+    /// client has given up reconnecting.
+    pub async fn next_event(&self) -> Option<WebSocketEvent> {
+        self.events.recv_async().await.ok()
+    }
+}
+
+struct SpotProtocol {
+    sequences: SequenceTracker,
+    tokens: Option<Box<dyn TokenProvider>>,
+    // The token fetched for the current connection.
+    token: Option<String>,
+}
+
+impl SpotProtocol {
+    // Swaps the token of a private subscription for the current one.
+    fn authenticated(&self, sub: &Subscription) -> Subscription {
+        let mut sub = sub.clone();
+        if let (Some(token), Some(_)) = (&self.token, &sub.subscription.token) {
+            sub.subscription.token = Some(token.clone());
+        }
+        sub
+    }
+}
+
+#[async_trait]
+impl Protocol for SpotProtocol {
+    type Subscription = Subscription;
+    type Message = Value;
+
+    async fn prepare(&mut self) -> Result<(), String> {
+        if let Some(tokens) = &self.tokens {
+            let token = tokens.token().await.map_err(|err| err.to_string())?;
+            self.token = Some(token);
+        }
+        Ok(())
+    }
+
+    fn on_connect(&mut self) -> Vec<Value> {
+        // Sequence numbers restart once channels are subscribed again.
+        self.sequences.reset();
+        Vec::new()
+    }
+
+    fn subscribe(&mut self, sub: &Subscription) -> Vec<Value> {
+        vec![self.authenticated(sub).subscribe_message()]
+    }
+
+    fn unsubscribe(&mut self, sub: &Subscription) -> Vec<Value> {
+        vec![self.authenticated(sub).unsubscribe_message()]
+    }
+
+    fn on_message(&mut self, msg: Value) -> Handled<Value> {
+        let mut handled = Handled::default();
+        if msg.get("event").and_then(Value::as_str) == Some("heartbeat") {
+            return handled;
+        }
+        if let Some(gap) = self.sequences.observe_message(&msg) {
+            handled.events.push(WebSocketEvent::GapDetected(gap));
+        }
+        handled.events.push(WebSocketEvent::Message(msg));
+        handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WebSocketClient, WebSocketEvent};
+    use crate::kraken::websocket::{ReconnectPolicy, Subscription, TokenProvider};
+    use crate::kraken::ClientError;
+    use async_trait::async_trait;
+    use futures_util::{SinkExt, StreamExt};
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::WebSocketStream;
+    use url::Url;
+
+    fn fast_policy() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            max_attempts: Some(5),
+            ..ReconnectPolicy::default()
+        }
+    }
+
+    // Hands out "token-1", "token-2" and so on.
+    #[derive(Default)]
+    struct CountingTokens(AtomicU32);
+
+    #[async_trait]
+    impl TokenProvider for CountingTokens {
+        async fn token(&self) -> Result<String, ClientError> {
+            let count = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("token-{}", count))
+        }
+    }
+
+    #[test]
+    fn creates_clients_outside_a_runtime() {
+        let url = Url::parse("ws://127.0.0.1:1").unwrap();
+        let connecting = WebSocketClient::connect(url, fast_policy());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let client = runtime.block_on(connecting);
+        let event = runtime.block_on(client.next_event());
+        assert!(matches!(event, Some(WebSocketEvent::Closed)));
+    }
+
+    async fn next_text(socket: &mut WebSocketStream<TcpStream>) -> Value {
+        loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn resubscribes_and_detects_gaps_after_reconnecting() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let sub = Subscription::private("openOrders", "expired".to_owned());
+        let server = tokio::spawn(async move {
+            // The first connection receives the subscription, then drops.
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let first = Subscription::private("openOrders", "token-1".to_owned());
+            assert_eq!(next_text(&mut socket).await, first.subscribe_message());
+            drop(socket);
+            // The second connection must replay it without being
+            // asked, with the token fetched for that connection.
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let second = Subscription::private("openOrders", "token-2".to_owned());
+            assert_eq!(next_text(&mut socket).await, second.subscribe_message());
+            for seq in [1, 3] {
+                let msg = json!([[], "openOrders", {"sequence": seq}]);
+                socket.send(Message::Text(msg.to_string())).await.unwrap();
+            }
+            // Keep the connection open until the client hangs up.
+            while socket.next().await.is_some() {}
+        });
+
+        let url = Url::parse(&format!("ws://{}", addr)).unwrap();
+        let tokens = CountingTokens::default();
+        let client = WebSocketClient::connect_private(url, fast_policy(), tokens).await;
+        client.subscribe(sub);
+        assert!(matches!(
+            client.next_event().await,
+            Some(WebSocketEvent::Connected)
+        ));
+        assert!(matches!(
+            client.next_event().await,
+            Some(WebSocketEvent::Disconnected { .. })
+        ));
+        assert!(matches!(
+            client.next_event().await,
+            Some(WebSocketEvent::Reconnected { attempts: 1 })
+        ));
+        assert!(matches!(
+            client.next_event().await,
+            Some(WebSocketEvent::Message(_))
+        ));
+        match client.next_event().await {
+            Some(WebSocketEvent::GapDetected(gap)) => {
+                assert_eq!(gap.expected, 2);
+                assert_eq!(gap.received, 3);
+            }
+            other => panic!("expected a gap, got {:?}", other),
+        }
+        drop(client);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn closes_after_max_attempts() {
+        // Bind and immediately drop a listener to find a closed port.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let url = Url::parse(&format!("ws://{}", addr)).unwrap();
+        let client = WebSocketClient::connect(url, fast_policy()).await;
+        assert!(matches!(
+            client.next_event().await,
+            Some(WebSocketEvent::Closed)
+        ));
+        assert!(client.next_event().await.is_none());
+    }
+}
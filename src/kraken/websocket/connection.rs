@@ -0,0 +1,250 @@
+use super::{ReconnectPolicy, WebSocketEvent};
+use crate::kraken::trace;
+use async_trait::async_trait;
+use flume::{Receiver, Sender};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use url::Url;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type CommandSender<P> = Sender<Command<<P as Protocol>::Subscription>>;
+type EventReceiver<P> = Receiver<WebSocketEvent<<P as Protocol>::Message>>;
+
+/// Protocol adapts the reconnecting connection to the message
+/// format of one of Kraken's websocket APIs.
+#[async_trait]
+pub trait Protocol: Send + 'static {
+    type Subscription: Clone + PartialEq + Send + 'static;
+    type Message: Send + 'static;
+
+    /// Called before every connection attempt, such as to fetch fresh
+    /// credentials. An error counts as a failed attempt.
+    async fn prepare(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called whenever a new connection opens, before any subscriptions
+    /// are replayed. Returns the messages to send first.
+    fn on_connect(&mut self) -> Vec<Value>;
+    /// Returns the messages that subscribe to the channel.
+    fn subscribe(&mut self, sub: &Self::Subscription) -> Vec<Value>;
+    /// Returns the messages that unsubscribe from the channel.
+    fn unsubscribe(&mut self, sub: &Self::Subscription) -> Vec<Value>;
+    /// Interprets a message from Kraken, returning any replies to
+    /// send and the events to pass on to the consumer.
+    fn on_message(&mut self, msg: Value) -> Handled<Self::Message>;
+}
+
+pub struct Handled<M> {
+    pub replies: Vec<Value>,
+    pub events: Vec<WebSocketEvent<M>>,
+}
+
+impl<M> Default for Handled<M> {
+    fn default() -> Self {
+        Self {
+            replies: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+pub enum Command<S> {
+    Subscribe(S),
+    Unsubscribe(S),
+}
+
+/// Spawns the background task that owns the connection onto the
+/// current Tokio runtime, panicking outside one, which is why the
+/// clients only call it from async constructors. The task stops once
+/// the command sender is dropped or the ReconnectPolicy gives up.
+pub fn spawn<P: Protocol>(
+    url: Url,
+    policy: ReconnectPolicy,
+    protocol: P,
+) -> (CommandSender<P>, EventReceiver<P>) {
+    let (commands, command_recv) = flume::unbounded();
+    let (event_sender, events) = flume::unbounded();
+    let worker = Worker {
+        url,
+        policy,
+        protocol,
+        commands: command_recv,
+        events: event_sender,
+        subscriptions: Vec::new(),
+    };
+    tokio::spawn(worker.run());
+    (commands, events)
+}
+
+enum SessionEnd {
+    Dropped(String),
+    Shutdown,
+}
+
+struct Worker<P: Protocol> {
+    url: Url,
+    policy: ReconnectPolicy,
+    protocol: P,
+    commands: Receiver<Command<P::Subscription>>,
+    events: Sender<WebSocketEvent<P::Message>>,
+    subscriptions: Vec<P::Subscription>,
+}
+
+impl<P: Protocol> Worker<P> {
+    async fn run(mut self) {
+        let mut connected_before = false;
+        let mut attempt = 0;
+        loop {
+            if let Some(socket) = self.open().await {
+                let event = if connected_before {
+                    WebSocketEvent::Reconnected { attempts: attempt }
+                } else {
+                    WebSocketEvent::Connected
+                };
+                connected_before = true;
+                attempt = 0;
+                match self.session(socket, event).await {
+                    SessionEnd::Shutdown => return,
+                    SessionEnd::Dropped(reason) => {
+                        self.emit(WebSocketEvent::Disconnected { reason })
+                    }
+                }
+            }
+            attempt += 1;
+            if !self.policy.should_retry(attempt) {
+                self.emit(WebSocketEvent::Closed);
+                return;
+            }
+            if !self.wait(attempt).await {
+                return;
+            }
+        }
+    }
+
+    // Prepares the protocol and opens a connection,
+    // returning None if either fails.
+    async fn open(&mut self) -> Option<Socket> {
+        if let Err(_reason) = self.protocol.prepare().await {
+            trace::warning!(reason = %_reason, "failed to prepare the websocket connection");
+            return None;
+        }
+        let (socket, _) = connect_async(self.url.as_str()).await.ok()?;
+        Some(socket)
+    }
+
+    async fn session(
+        &mut self,
+        mut socket: Socket,
+        event: WebSocketEvent<P::Message>,
+    ) -> SessionEnd {
+        let mut replay = self.protocol.on_connect();
+        for sub in &self.subscriptions {
+            replay.extend(self.protocol.subscribe(sub));
+        }
+        if let Err(reason) = send_all(&mut socket, replay).await {
+            return SessionEnd::Dropped(reason);
+        }
+        self.emit(event);
+        let commands = self.commands.clone();
+        loop {
+            tokio::select! {
+                cmd = commands.recv_async() => {
+                    let msgs = match cmd {
+                        Ok(cmd) => self.apply(cmd),
+                        Err(_) => {
+                            let _ = socket.close(None).await;
+                            return SessionEnd::Shutdown;
+                        }
+                    };
+                    if let Err(reason) = send_all(&mut socket, msgs).await {
+                        return SessionEnd::Dropped(reason);
+                    }
+                }
+                msg = tokio::time::timeout(self.policy.idle_timeout, socket.next()) => {
+                    let reason = match msg {
+                        Ok(Some(Ok(Message::Text(text)))) => {
+                            let replies = self.handle(&text);
+                            match send_all(&mut socket, replies).await {
+                                Ok(()) => continue,
+                                Err(reason) => reason,
+                            }
+                        }
+                        Ok(Some(Ok(Message::Close(_)))) | Ok(None) => {
+                            "connection closed by server".to_owned()
+                        }
+                        Ok(Some(Ok(_))) => continue,
+                        Ok(Some(Err(err))) => err.to_string(),
+                        Err(_) => "no message received within the idle timeout".to_owned(),
+                    };
+                    return SessionEnd::Dropped(reason);
+                }
+            }
+        }
+    }
+
+    // Waits out the backoff delay, still accepting subscription
+    // changes so they are applied on the next connection.
+    // Returns false if the client was dropped in the meantime.
+    async fn wait(&mut self, attempt: u32) -> bool {
+        let sleep = tokio::time::sleep(self.policy.delay(attempt));
+        tokio::pin!(sleep);
+        let commands = self.commands.clone();
+        loop {
+            tokio::select! {
+                _ = &mut sleep => return true,
+                cmd = commands.recv_async() => match cmd {
+                    Ok(cmd) => {
+                        self.apply(cmd);
+                    }
+                    Err(_) => return false,
+                },
+            }
+        }
+    }
+
+    // Updates the set of active subscriptions, returning
+    // the messages to send to Kraken.
+    fn apply(&mut self, cmd: Command<P::Subscription>) -> Vec<Value> {
+        match cmd {
+            Command::Subscribe(sub) => {
+                let msgs = self.protocol.subscribe(&sub);
+                if !self.subscriptions.contains(&sub) {
+                    self.subscriptions.push(sub);
+                }
+                msgs
+            }
+            Command::Unsubscribe(sub) => {
+                self.subscriptions.retain(|active| active != &sub);
+                self.protocol.unsubscribe(&sub)
+            }
+        }
+    }
+
+    fn handle(&mut self, text: &str) -> Vec<Value> {
+        let msg: Value = match serde_json::from_str(text) {
+            Ok(msg) => msg,
+            Err(_) => return Vec::new(),
+        };
+        let handled = self.protocol.on_message(msg);
+        for event in handled.events {
+            self.emit(event);
+        }
+        handled.replies
+    }
+
+    fn emit(&self, event: WebSocketEvent<P::Message>) {
+        let _ = self.events.send(event);
+    }
+}
+
+async fn send_all(socket: &mut Socket, msgs: Vec<Value>) -> Result<(), String> {
+    for msg in msgs {
+        let msg = Message::Text(msg.to_string());
+        socket.send(msg).await.map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
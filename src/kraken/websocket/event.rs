@@ -0,0 +1,26 @@
+use super::SequenceGap;
+use serde_json::Value;
+
+/// WebSocketEvent is everything a websocket client reports
+/// to its consumer, including changes in connection state.
+/// `M` is the type of the messages received from Kraken.
+#[derive(Debug, Clone)]
+pub enum WebSocketEvent<M = Value> {
+    /// The first connection was established.
+    Connected,
+    /// The connection dropped. The client will try to reconnect
+    /// according to its ReconnectPolicy.
+    Disconnected { reason: String },
+    /// The connection was reestablished after the given number of
+    /// attempts and every active subscription has been replayed.
+    /// Any state built from previous messages, such as an order book,
+    /// should be rebuilt from the fresh snapshots.
+    Reconnected { attempts: u32 },
+    /// A sequenced channel skipped sequence numbers, so at least one
+    /// update was missed. Consumers should re-query the affected state.
+    GapDetected(SequenceGap),
+    /// A message received from Kraken. Heartbeats are not forwarded.
+    Message(M),
+    /// The client gave up reconnecting and will not emit any more events.
+    Closed,
+}
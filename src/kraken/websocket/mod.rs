@@ -0,0 +1,15 @@
+pub use client::WebSocketClient;
+pub use event::WebSocketEvent;
+pub use reconnect_policy::ReconnectPolicy;
+pub use sequence::{SequenceGap, SequenceTracker};
+pub use subscription::{Subscription, SubscriptionOptions};
+pub use token_provider::TokenProvider;
+
+pub(crate) mod connection;
+
+mod client;
+mod event;
+mod reconnect_policy;
+mod sequence;
+mod subscription;
+mod token_provider;
@@ -0,0 +1,110 @@
+use crate::kraken::backoff::Backoff;
+use std::time::Duration;
+
+/// ReconnectPolicy describes how long the websocket client waits
+/// between connection attempts after a feed drops. The delay grows
+/// exponentially with each failed attempt, is capped at `max_delay`,
+/// and is spread out by a random jitter so that many clients
+/// disconnected at once don't reconnect in lockstep.
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    /// The delay before the first reconnection attempt.
+    pub initial_delay: Duration,
+    /// The upper bound on the delay between attempts.
+    pub max_delay: Duration,
+    /// The factor the delay grows by after each failed attempt.
+    pub multiplier: f64,
+    /// The fraction of the delay, between 0 and 1, that is randomized.
+    pub jitter: f64,
+    /// How many consecutive failed attempts are tolerated before
+    /// giving up. None retries forever.
+    pub max_attempts: Option<u32>,
+    /// How long the connection may go without receiving any message
+    /// before it is considered dead. Kraken sends a heartbeat every
+    /// second, so silence is a reliable sign of a stale connection.
+    pub idle_timeout: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.25,
+            max_attempts: None,
+            idle_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Returns the delay to wait before the given attempt,
+    /// counting from 1, including jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let backoff = Backoff {
+            initial_delay: self.initial_delay,
+            max_delay: self.max_delay,
+            multiplier: self.multiplier,
+            jitter: self.jitter,
+        };
+        backoff.delay(attempt)
+    }
+
+    /// Returns true if another attempt is allowed after
+    /// `attempt` consecutive failures.
+    pub fn should_retry(&self, attempt: u32) -> bool {
+        match self.max_attempts {
+            Some(max) => attempt < max,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReconnectPolicy;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn policy() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: Some(3),
+            idle_timeout: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn grows_exponentially_up_to_the_cap() {
+        let policy = policy();
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(2), Duration::from_secs(2));
+        assert_eq!(policy.delay(4), Duration::from_secs(8));
+        assert_eq!(policy.delay(5), Duration::from_secs(10));
+        assert_eq!(policy.delay(30), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = ReconnectPolicy {
+            jitter: 0.5,
+            ..policy()
+        };
+        for _ in 0..100 {
+            let delay = policy.delay(3);
+            assert!(delay <= Duration::from_secs(4));
+            assert!(delay >= Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn stops_after_max_attempts() {
+        let policy = policy();
+        assert!(policy.should_retry(2));
+        assert!(!policy.should_retry(3));
+    }
+}
@@ -0,0 +1,117 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// SequenceGap describes a jump in the sequence numbers
+/// of a private channel, meaning at least one message was missed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceGap {
+    pub channel: String,
+    pub expected: u64,
+    pub received: u64,
+}
+
+/// SequenceTracker remembers the last sequence number seen on each
+/// private channel (`ownTrades`, `openOrders`) and reports when
+/// a message arrives out of order.
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+    last_seen: HashMap<String, u64>,
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the sequence number for the channel, returning
+    /// a SequenceGap if any sequence numbers were skipped.
+    pub fn observe(&mut self, channel: &str, sequence: u64) -> Option<SequenceGap> {
+        let previous = self.last_seen.insert(channel.to_owned(), sequence);
+        match previous {
+            Some(last) if sequence > last + 1 => Some(SequenceGap {
+                channel: channel.to_owned(),
+                expected: last + 1,
+                received: sequence,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Sets the sequence number for the channel without checking for
+    /// a gap, as when a snapshot establishes a new starting point.
+    pub fn start(&mut self, channel: &str, sequence: u64) {
+        self.last_seen.insert(channel.to_owned(), sequence);
+    }
+
+    /// Inspects a raw message and, if it belongs to a sequenced
+    /// channel, records its sequence number.
+    pub fn observe_message(&mut self, msg: &Value) -> Option<SequenceGap> {
+        let (channel, sequence) = sequence_of(msg)?;
+        self.observe(channel, sequence)
+    }
+
+    /// Forgets every channel. Kraken restarts sequence numbers
+    /// when a channel is subscribed again on a new connection.
+    pub fn reset(&mut self) {
+        self.last_seen.clear();
+    }
+}
+
+// Private channel messages have the shape
+// [payload, channelName, {"sequence": n}].
+fn sequence_of(msg: &Value) -> Option<(&str, u64)> {
+    let array = msg.as_array()?;
+    let sequence = array.last()?.get("sequence")?.as_u64()?;
+    let channel = array.get(array.len().checked_sub(2)?)?.as_str()?;
+    Some((channel, sequence))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SequenceGap, SequenceTracker};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn consecutive_messages_have_no_gap() {
+        let mut tracker = SequenceTracker::new();
+        assert_eq!(tracker.observe("openOrders", 1), None);
+        assert_eq!(tracker.observe("openOrders", 2), None);
+        assert_eq!(tracker.observe("ownTrades", 1), None);
+    }
+
+    #[test]
+    fn detects_skipped_messages() {
+        let mut tracker = SequenceTracker::new();
+        tracker.observe("openOrders", 1);
+        let gap = tracker.observe("openOrders", 4);
+        let expected = SequenceGap {
+            channel: "openOrders".to_owned(),
+            expected: 2,
+            received: 4,
+        };
+        assert_eq!(gap, Some(expected));
+    }
+
+    #[test]
+    fn reads_sequence_from_private_messages() {
+        let mut tracker = SequenceTracker::new();
+        let first =
+            json!([[{"OGTT3Y-C6I3P-XRI6HX": {"status": "closed"}}], "openOrders", {"sequence": 1}]);
+        let third =
+            json!([[{"OGTT3Y-C6I3P-XRI6HX": {"status": "open"}}], "openOrders", {"sequence": 3}]);
+        assert_eq!(tracker.observe_message(&first), None);
+        assert_eq!(tracker.observe_message(&third).unwrap().expected, 2);
+    }
+
+    #[test]
+    fn ignores_public_messages() {
+        let mut tracker = SequenceTracker::new();
+        let ticker = json!([340, {"a": ["5525.40000", 1, "1.000"]}, "ticker", "XBT/USD"]);
+        assert_eq!(tracker.observe_message(&ticker), None);
+        assert_eq!(
+            tracker.observe_message(&json!({"event": "heartbeat"})),
+            None
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Subscription is a single channel subscription on Kraken's websocket
+/// API. The client remembers every active Subscription so it can
+/// replay them after reconnecting.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Subscription {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub pair: Vec<String>,
+    pub subscription: SubscriptionOptions,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SubscriptionOptions {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl Subscription {
+    /// Builds a subscription to a public channel such
+    /// as `ticker`, `spread` or `book` for the given pairs.
+    pub fn public(name: &str, pairs: Vec<String>) -> Self {
+        Self {
+            pair: pairs,
+            subscription: SubscriptionOptions {
+                name: name.to_owned(),
+                depth: None,
+                interval: None,
+                token: None,
+            },
+        }
+    }
+
+    /// Builds a subscription to a private channel such as
+    /// `ownTrades` or `openOrders`, authenticated by a
+    /// token from the GetWebSocketsToken endpoint.
+    pub fn private(name: &str, token: String) -> Self {
+        let mut sub = Self::public(name, Vec::new());
+        sub.subscription.token = Some(token);
+        sub
+    }
+
+    pub fn subscribe_message(&self) -> Value {
+        self.message("subscribe")
+    }
+
+    pub fn unsubscribe_message(&self) -> Value {
+        self.message("unsubscribe")
+    }
+
+    fn message(&self, event: &str) -> Value {
+        let mut msg = json!(self);
+        msg["event"] = json!(event);
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Subscription;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_public_subscription() {
+        let sub = Subscription::public("ticker", vec!["XBT/USD".to_owned()]);
+        let expected = json!({
+            "event": "subscribe",
+            "pair": ["XBT/USD"],
+            "subscription": {"name": "ticker"}
+        });
+        assert_eq!(sub.subscribe_message(), expected);
+    }
+
+    #[test]
+    fn serializes_private_unsubscription() {
+        let sub = Subscription::private("openOrders", "WW91ciBhdXRoZW50aWNh".to_owned());
+        let expected = json!({
+            "event": "unsubscribe",
+            "subscription": {"name": "openOrders", "token": "WW91ciBhdXRoZW50aWNh"}
+        });
+        assert_eq!(sub.unsubscribe_message(), expected);
+    }
+}
@@ -0,0 +1,21 @@
+use crate::kraken::ClientError;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// TokenProvider fetches the token which authenticates private
+/// subscriptions, such as by calling the GetWebSocketsToken endpoint.
+/// Kraken expires a token that no connection has used within 15 minutes,
+/// so a WebSocketClient asks for a fresh one before every connection
+/// rather than replaying the token a subscription was first made with.
+#[async_trait]
+pub trait TokenProvider: Send + Sync {
+    async fn token(&self) -> Result<String, ClientError>;
+}
+
+// Lets one provider be shared between several clients.
+#[async_trait]
+impl<P: TokenProvider + ?Sized> TokenProvider for Arc<P> {
+    async fn token(&self) -> Result<String, ClientError> {
+        (**self).token().await
+    }
+}
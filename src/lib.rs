@@ -1,2 +1,17 @@
-pub use self::kraken::{endpoint, AccountTier, AssetPair, Client, KrakenCredentials, TickerInfo};
+#[allow(deprecated)]
+pub use self::kraken::endpoint;
+#[cfg(feature = "testing")]
+pub use self::kraken::testing;
+pub use self::kraken::{
+    endpoint_cost, AccountTier, AddOrderInput, AddOrderResponse, AddOrderResult, Asset, AssetInfo,
+    AssetNormalizer, AssetPair, AssetPairInfo, AssetPairsInfo, AssetPairsResponse, AssetPairsShape,
+    AssetStatus, AtomicNonce, Authenticated, CancelOrderResponse, CancelOrderResult, Client,
+    ClientBuilder, ClientError, CounterLimit, FileNonce, FileRateLimiter, InvalidLimitError,
+    KrakenCredentials, LeakyBucket, Middleware, NonceProvider, OrderDescription, OrderError,
+    OrderSide, OrderType, OrderValidator, PairFees, PairLeverage, PairMargin, PairRegistry,
+    PairStatus, ParsePairError, Public, PublicClient, RateLimiter, RecentSpreadsResponse,
+    RequestInfo, ResponseInfo, RetryPolicy, Spread, TickerInfo, TickerResponse,
+    DEFAULT_API_VERSION, DEFAULT_BASE_URL,
+};
+pub use self::kraken::{futures, websocket};
 mod kraken;